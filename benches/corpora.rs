@@ -0,0 +1,116 @@
+// Times full_solve on every puzzle of the checked-in corpora (latin/latin_*.txt, towers_*.txt and
+// loopy/loopy_*.txt), per family and size, and counts how many puzzles have a unique solution,
+// several solutions or none. Every corpus puzzle is meant to be unique, so anything else is a bug.
+// Run with `cargo bench --bench corpora`. Arguments pick which corpora to time, by family or by
+// name: `cargo bench --bench corpora -- loopy towers_6` times every loopy corpus and towers_6.
+// BENCH_ROUNDS sets how many times every puzzle is solved (1 by default).
+//
+// On one core a round of everything but towers_9 takes about three minutes, most of it towers_8.
+// towers_9 takes over 25 minutes, so it's only timed when asked for by name.
+
+use puzzle_solvers::corpus;
+use puzzle_solvers::latin;
+use puzzle_solvers::loopy;
+use puzzle_solvers::puzzle::Puzzle;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::towers;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+// Corpora too slow to time unless asked for by name.
+const SLOW: [&str; 1] = ["towers_9"];
+
+// The smallest time at least p percent of the times are below or equal to. times must be sorted.
+fn percentile(times: &[Duration], p: usize) -> Duration {
+    let rank = (times.len() * p).div_ceil(100).max(1);
+    return times[rank - 1];
+}
+
+fn ms(d: Duration) -> f64 {
+    return d.as_secs_f64() * 1000.0;
+}
+
+// Solves every puzzle of the corpus file rounds times and prints a line of statistics.
+// Returns false if some puzzle didn't have a unique solution.
+fn time<S: PuzzleSolver>(name: &str, path: &Path, rounds: usize) -> bool {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return true,
+    };
+    let puzzles: Vec<S::Puzzle> = corpus::read_entries(&content).iter().map(|e| S::Puzzle::from_string(&e.body).unwrap()).collect();
+    if puzzles.is_empty() {
+        return true;
+    }
+
+    let mut times: Vec<Duration> = Vec::new();
+    let (mut unique, mut multiple, mut unsolvable) = (0, 0, 0);
+    for round in 0..rounds {
+        for p in &puzzles {
+            let start = Instant::now();
+            let mut s = S::new(p.clone());
+            let solutions = s.full_solve(0, false);
+            times.push(start.elapsed());
+            if round == 0 {
+                match solutions.len() {
+                    0 => unsolvable += 1,
+                    1 => unique += 1,
+                    _ => multiple += 1,
+                }
+            }
+        }
+    }
+    times.sort();
+    let mean = ms(times.iter().sum()) / times.len() as f64;
+    println!(
+        "{:<10} {:>4} {:>9.3} {:>9.3} {:>9.3} {:>9.3} {:>9.3} {:>7} {:>8} {:>10}",
+        name, puzzles.len(), mean,
+        ms(percentile(&times, 50)), ms(percentile(&times, 90)), ms(percentile(&times, 99)), ms(times[times.len() - 1]),
+        unique, multiple, unsolvable);
+    return multiple == 0 && unsolvable == 0;
+}
+
+fn main() {
+    // cargo passes --bench to benchmarks without a harness.
+    let filters: Vec<String> = env::args().skip(1).filter(|a| !a.starts_with('-')).collect();
+    let wanted = |name: &str| {
+        if filters.is_empty() {
+            return !SLOW.contains(&name);
+        }
+        return filters.iter().any(|f| name == f || (name.starts_with(&format!("{}_", f)) && !SLOW.contains(&name)));
+    };
+    let rounds = match env::var("BENCH_ROUNDS") {
+        Ok(r) => r.parse().expect("BENCH_ROUNDS should be a number"),
+        Err(_) => 1,
+    };
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    println!("Times in ms, over {} round(s).", rounds);
+    println!("{:<10} {:>4} {:>9} {:>9} {:>9} {:>9} {:>9} {:>7} {:>8} {:>10}", "corpus", "n", "mean", "p50", "p90", "p99", "max", "unique", "multiple", "unsolvable");
+    let mut all_unique = true;
+    for size in [2, 3, 4, 5, 6, 7, 8, 9, 12] {
+        let name = format!("latin_{}", size);
+        if wanted(&name) {
+            all_unique &= time::<latin::solver::Solver>(&name, &root.join(format!("latin/latin_{}.txt", size)), rounds);
+        }
+    }
+    for size in 2..=9 {
+        let name = format!("towers_{}", size);
+        if wanted(&name) {
+            all_unique &= time::<towers::solver::Solver>(&name, &root.join(format!("towers_{}.txt", size)), rounds);
+        }
+    }
+    for size in 2..=10 {
+        let name = format!("loopy_{}", size);
+        if wanted(&name) {
+            all_unique &= time::<loopy::solver::Solver>(&name, &root.join(format!("loopy/loopy_{}.txt", size)), rounds);
+        }
+    }
+    if !all_unique {
+        eprintln!("Some corpus puzzles don't have a unique solution.");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,104 @@
+// Times the loopy solver on the loopy/loopy_*.txt corpora, on the 20x20 sample from main.rs and on
+// a 15x15 puzzle that needs several guesses.
+// Run with `cargo bench --bench loopy_solver`.
+//
+// The means change from run to run and from machine to machine, so compare runs before and after a
+// change on the same machine. Keeping the edge state in bitsets instead of HashSets (3898cbd) was
+// measured that way: this file was copied into a checkout of the commit before it, with a [[bench]]
+// entry added to its Cargo.toml, and the two checkouts were run one after the other five times
+// each, on one core of an Intel Xeon with rustc 1.95.0. The median means, in ms:
+//
+//                  HashSets   bitsets
+//     loopy_2         0.043     0.027
+//     loopy_3         0.149     0.059
+//     loopy_4         0.239     0.164
+//     loopy_5         0.756     0.400
+//     loopy_6         1.656     0.695
+//     loopy_7         4.658     1.959
+//     loopy_8        10.424     4.706
+//     loopy_9        24.555     8.265
+//     loopy_10        5.826     2.555
+//     20x20 sample   56.661    16.720
+//     15x15 sample  491.468   198.228
+//
+// The HashSet version guesses in HashSet order, so its 15x15 sample took from 283 to 774 ms.
+
+use puzzle_solvers::corpus;
+use puzzle_solvers::loopy::puzzle::Puzzle;
+use puzzle_solvers::loopy::solver::Solver;
+
+use std::fs;
+use std::time::Duration;
+use std::time::Instant;
+
+const ROUNDS: usize = 3;
+
+const SAMPLE_20: &str = "\
+..33.3.1.22..3..23.3
+3.2.1.3..23.3.22.2..
+3...3.2.22.....21.32
+...2....221..2...22.
+13...132.32....0.21.
+32...2.2....2..322.2
+.3.2322....122..1...
+.21112222..1..33..2.
+....221.21.2.1....3.
+..2.21.3.....12.213.
+22.33..2.1...212...2
+...2...2...2.3.22...
+232...23..2213......
+..21.20....3.322222.
+231....312..2111.323
+......1..312.33....2
+3..22.1323.....122..
+..3222.2..212.3..2.1
+33.2....3....2....2.
+.........23...33.213";
+
+// Generated with `generate -t loopy -s 15 -r 3`.
+const SAMPLE_15: &str = "\
+...23..12.20..0
+300....23..1..1
+...2..2.2..2..2
+...3.11...2.3.2
+10.2...0..3..11
+..1...1.3.31...
+22...3..3..22..
+...3..0......1.
+.0...........2.
+.32...33.201.20
+....1...3.20...
+0..0.........0.
+....0...110....
+.332..0......31
+.....33....21.3";
+
+// Solves every puzzle ROUNDS times and prints the mean time of a solve and the slowest one.
+fn time(name: &str, puzzles: &[Puzzle]) {
+    let mut total = Duration::ZERO;
+    let mut slowest = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        for p in puzzles {
+            let start = Instant::now();
+            let mut s = Solver::new(p.clone());
+            let solutions = s.full_solve(0, false);
+            let elapsed = start.elapsed();
+            assert_eq!(solutions.len(), 1);
+            total += elapsed;
+            slowest = slowest.max(elapsed);
+        }
+    }
+    let mean = total.as_secs_f64() * 1000.0 / (ROUNDS * puzzles.len()) as f64;
+    println!("{:<12} {:>4} puzzles  mean {:>9.3} ms  slowest {:>9.3} ms", name, puzzles.len(), mean, slowest.as_secs_f64() * 1000.0);
+}
+
+fn main() {
+    for size in 2..=10 {
+        let path = format!("{}/loopy/loopy_{}.txt", env!("CARGO_MANIFEST_DIR"), size);
+        let content = fs::read_to_string(&path).expect("could not read the loopy corpus");
+        let puzzles: Vec<Puzzle> = corpus::read_entries(&content).iter().map(|e| Puzzle::from_string(&e.body).unwrap()).collect();
+        time(&format!("loopy_{}", size), &puzzles);
+    }
+    time("20x20 sample", &[Puzzle::from_string(SAMPLE_20).unwrap()]);
+    time("15x15 sample", &[Puzzle::from_string(SAMPLE_15).unwrap()]);
+}
@@ -0,0 +1,95 @@
+// A set of the integers below a fixed bound, one bit each. Cloning, unions and intersections work
+// a word at a time, which makes it a lot cheaper than a HashSet for the solvers' dense state.
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    // The empty set of integers below size.
+    pub fn new(size: usize) -> BitSet {
+        return BitSet { words: vec![0; size.div_ceil(64)] };
+    }
+
+    // The set of every integer below size.
+    pub fn full(size: usize) -> BitSet {
+        let mut s = BitSet::new(size);
+        for (i, w) in s.words.iter_mut().enumerate() {
+            let left = size - 64 * i;
+            *w = if left >= 64 { u64::MAX } else { (1 << left) - 1 };
+        }
+        return s;
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        return self.words[i / 64] & (1 << (i % 64)) != 0;
+    }
+
+    // Whether i was not already in the set.
+    pub fn insert(&mut self, i: usize) -> bool {
+        let w = &mut self.words[i / 64];
+        let bit = 1 << (i % 64);
+        let added = *w & bit == 0;
+        *w |= bit;
+        return added;
+    }
+
+    // Whether i was in the set.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let w = &mut self.words[i / 64];
+        let bit = 1 << (i % 64);
+        let removed = *w & bit != 0;
+        *w &= !bit;
+        return removed;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.words.iter().map(|w| w.count_ones() as usize).sum();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.words.iter().all(|w| *w == 0);
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    // The members in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        return self.words.iter().enumerate().flat_map(|(i, w)| {
+            let mut w = *w;
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let bit = w.trailing_zeros() as usize;
+                w &= w - 1;
+                return Some(64 * i + bit);
+            })
+        });
+    }
+
+    // Whether anything was added.
+    pub fn union_with(&mut self, other: &BitSet) -> bool {
+        let mut changed = false;
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            changed |= *o & !*w != 0;
+            *w |= *o;
+        }
+        return changed;
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w &= *o;
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w &= !*o;
+        }
+    }
+}
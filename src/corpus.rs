@@ -0,0 +1,91 @@
+// Reading the plain text puzzle files that the generators write out, e.g. latin/latin_4.txt,
+// towers_4.txt or loopy/loopy_4.txt.
+//
+// Entries are separated by blank lines or by "-----" lines. An entry can be preceded by a
+// "Difficulty: N" line, or by a bare number on the line right before a Tatham game ID (which is
+// the format of the latin files), and by a "Seed: N" line recording the seed it was generated
+// from. A difficulty followed by "(varies)" was not the same on every run of the solver that
+// recorded it. A line containing a ':' is a Tatham game ID and is always an entry on its own;
+// anything else is a row of a multi-line grid.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub difficulty: Option<u8>,
+    pub difficulty_varies: bool,
+    pub seed: Option<u64>,
+    pub body: String,
+}
+
+fn is_game_id(line: &str) -> bool {
+    return line.contains(':') && !line.starts_with("Difficulty:") && !line.starts_with("Seed:");
+}
+
+// The difficulty the line records, and whether it is marked as varying.
+fn parse_difficulty(line: &str, next_line: Option<&&str>) -> Option<(u8, bool)> {
+    if let Some(value) = line.strip_prefix("Difficulty:") {
+        let value = value.trim();
+        return match value.strip_suffix("(varies)") {
+            Some(x) => x.trim().parse::<u8>().ok().map(|d| (d, true)),
+            None => value.parse::<u8>().ok().map(|d| (d, false)),
+        };
+    }
+    let followed_by_game_id = match next_line {
+        Some(x) => is_game_id(x.trim()),
+        None => false,
+    };
+    if followed_by_game_id && !line.is_empty() && line.chars().all(|c| c.is_ascii_digit()) {
+        return line.parse::<u8>().ok().map(|d| (d, false));
+    }
+    return None;
+}
+
+pub fn read_entries(s: &str) -> Vec<Entry> {
+    let lines: Vec<&str> = s.lines().collect();
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut difficulty: Option<(u8, bool)> = None;
+    let mut seed: Option<u64> = None;
+    let mut body: Vec<&str> = Vec::new();
+
+    let mut flush = |difficulty: &mut Option<(u8, bool)>, seed: &mut Option<u64>, body: &mut Vec<&str>| {
+        if !body.is_empty() {
+            entries.push(Entry {
+                difficulty: difficulty.map(|x| x.0),
+                difficulty_varies: difficulty.is_some_and(|x| x.1),
+                seed: *seed,
+                body: body.join("\n"),
+            });
+            body.clear();
+            *difficulty = None;
+            *seed = None;
+        }
+    };
+
+    for i in 0..lines.len() {
+        let line = lines[i].trim_end();
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.chars().all(|c| c == '-') {
+            flush(&mut difficulty, &mut seed, &mut body);
+            continue;
+        }
+        if let Some(x) = parse_difficulty(trimmed, lines.get(i + 1)) {
+            flush(&mut difficulty, &mut seed, &mut body);
+            difficulty = Some(x);
+            continue;
+        }
+        if let Some(value) = trimmed.strip_prefix("Seed:") {
+            flush(&mut difficulty, &mut seed, &mut body);
+            seed = value.trim().parse::<u64>().ok();
+            continue;
+        }
+        if is_game_id(trimmed) {
+            flush(&mut difficulty, &mut seed, &mut body);
+            body.push(trimmed);
+            flush(&mut difficulty, &mut seed, &mut body);
+        } else {
+            body.push(line);
+        }
+    }
+    flush(&mut difficulty, &mut seed, &mut body);
+
+    return entries;
+}
@@ -0,0 +1,78 @@
+// Grades puzzles by the techniques their solver needed, instead of only by how deeply it had to
+// guess. The tiers follow the ones Simon Tatham's puzzles use.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    Easy,
+    Normal,
+    Hard,
+    Extreme,
+    // Needs guessing.
+    Unreasonable,
+}
+
+impl Tier {
+    pub fn all() -> [Tier; 5] {
+        return [Tier::Easy, Tier::Normal, Tier::Hard, Tier::Extreme, Tier::Unreasonable];
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{:?}", self);
+    }
+}
+
+impl FromStr for Tier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Tier, String> {
+        return match Tier::all().iter().find(|t| t.to_string().eq_ignore_ascii_case(s)) {
+            Some(t) => Ok(*t),
+            None => Err(format!("unknown difficulty tier '{}'", s)),
+        };
+    }
+}
+
+// A rule or technique a solver records when it uses it.
+// Techniques are ordered from easiest to hardest.
+pub trait Technique: Copy + Ord + fmt::Debug {
+    fn tier(&self) -> Tier;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grade {
+    pub tier: Tier,
+    // The name of the hardest technique used, if any was.
+    pub hardest: Option<String>,
+    // How many times each technique was used, from easiest to hardest.
+    pub counts: Vec<(String, usize)>,
+}
+
+// Grades the techniques a solver used, in any order.
+pub fn grade<T: Technique>(used: impl IntoIterator<Item = T>) -> Grade {
+    let mut counts: BTreeMap<T, usize> = BTreeMap::new();
+    for t in used {
+        *counts.entry(t).or_insert(0) += 1;
+    }
+    let hardest = counts.keys().max_by_key(|t| (t.tier(), **t)).cloned();
+    return Grade {
+        tier: hardest.map(|t| t.tier()).unwrap_or(Tier::Easy),
+        hardest: hardest.map(|t| format!("{:?}", t)),
+        counts: counts.iter().map(|(t, c)| (format!("{:?}", t), *c)).collect(),
+    };
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts: Vec<String> = self.counts.iter().map(|(t, c)| format!("{} {}", t, c)).collect();
+        return match &self.hardest {
+            Some(t) => write!(f, "{} (hardest: {}; {})", self.tier, t, counts.join(", ")),
+            None => write!(f, "{}", self.tier),
+        };
+    }
+}
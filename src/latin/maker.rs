@@ -1,51 +1,66 @@
+use crate::grader::Tier;
+use crate::maker;
 use crate::perm::permutation::random_perm;
 use super::puzzle::Puzzle;
 use super::solver::Solver;
+use super::uniform;
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
+use std::ops::RangeInclusive;
 
-pub fn random_filled(n: u8) -> Puzzle {
-    let mut rows:Vec<Vec<u8>> = Vec::new();
+// How random_filled picks its latin square.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    // One random row at a time. Fast, but some squares are more likely than others.
+    RowByRow,
+    // Every square is equally likely. See uniform.rs.
+    Uniform,
+}
 
-    // Any set of rows that satisfy the latin square rules can be extended to a full latin square.
-    // TODO: Learned this from a comment in Simon Tatham's implementation. I should find and link a proof.
-    // Create a full latin square by adding one row at a time.
-    for _i in 0..n {
-        rows.push(random_perm(n, &rows));
-    }
+pub fn random_filled<R: Rng + ?Sized>(n: u8, backend: Backend, rng: &mut R) -> Puzzle {
+    let rows: Vec<Vec<u8>> = match backend {
+        Backend::RowByRow => {
+            let mut rows:Vec<Vec<u8>> = Vec::new();
+
+            // Any set of rows that satisfy the latin square rules can be extended to a full latin square.
+            // TODO: Learned this from a comment in Simon Tatham's implementation. I should find and link a proof.
+            // Create a full latin square by adding one row at a time.
+            for _i in 0..n {
+                rows.push(random_perm(n, &rows, rng));
+            }
+            rows
+        },
+        Backend::Uniform => uniform::random_square(n, rng),
+    };
 
     let grid:Vec<Vec<Option<u8>>> = rows.iter().map(|row| row.iter().map(|val| Some(val.clone())).collect()).collect();
     return Puzzle { size: n as usize, grid, difficulty: 0 };
 }
 
-pub fn make_puzzle(size: u8) -> Puzzle {
+// A random latin square with every hint, and a random order to try removing its hints in.
+fn filled_with_removal_order<R: Rng + ?Sized>(size: u8, backend: Backend, rng: &mut R) -> (Puzzle, Vec<usize>) {
     // Start with a random latin square
-    let p: Puzzle = random_filled(size);
+    let p: Puzzle = random_filled(size, backend, rng);
 
-    let mut hints_to_remove: Vec<bool> = vec![false; p.number_of_hints()];
     let mut cell_hints: Vec<usize> = Vec::new();
-
     for i in 0..(size*size) as usize {
         cell_hints.push(i);
     }
-    let mut rng = thread_rng();
-    cell_hints.shuffle(&mut rng);
-
-    let mut difficulty = 0;
-
-    for i in cell_hints.iter() {
-        hints_to_remove[*i] = true;
-        let temp_puzzle = p.with_hints_removed(&hints_to_remove, difficulty);
-        let mut s = Solver::new(temp_puzzle);
-        let solutions = s.full_solve(0, false);
-        if solutions.len() > 1 {
-            // No longer uniquely solveable, don't remove this hint.
-            hints_to_remove[*i] = false;
-        } else {
-            // How hard was it to solve
-            difficulty = solutions[0].depth_needed;
-        }
-    }
-    return p.with_hints_removed(&hints_to_remove, difficulty);
+    cell_hints.shuffle(rng);
+
+    return (p, cell_hints);
+}
+
+pub fn make_puzzle<R: Rng + ?Sized>(size: u8, backend: Backend, rng: &mut R) -> Puzzle {
+    let (p, order) = filled_with_removal_order(size, backend, rng);
+    return maker::remove_hints::<Solver>(&p, &order);
+}
+
+// A puzzle whose grade is in tiers, or None if this attempt ended up too easy.
+pub fn make_puzzle_in_tiers<R: Rng + ?Sized>(
+    size: u8, backend: Backend, tiers: &RangeInclusive<Tier>, rng: &mut R,
+) -> Option<Puzzle> {
+    let (p, order) = filled_with_removal_order(size, backend, rng);
+    return maker::remove_hints_in_tiers::<Solver>(&p, &order, tiers);
 }
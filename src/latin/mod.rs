@@ -1,3 +1,6 @@
 pub mod maker;
 pub mod puzzle;
+pub mod reference;
 pub mod solver;
+pub mod uniform;
+pub mod verify;
@@ -1,21 +1,26 @@
-fn char_to_cell(c: char) -> Option<u8> {
+use crate::parse;
+use crate::parse::ParseError;
+use crate::parse::Reason;
+use crate::puzzle;
+use crate::puzzle::row;
+use crate::puzzle::column;
+use super::verify;
+
+// The latin solver keeps the candidates left along each row, column and value in a 64 bit mask.
+pub const MAX_SIZE: usize = 64;
+
+fn char_to_cell(line: &parse::Line, index: usize, n: usize) -> Result<Option<u8>, ParseError> {
+    let c = line.chars[index];
     if c == '.' || c == '·' {
-      return None;
+      return Ok(None);
     } else {
-      return Some((c.to_digit(10).unwrap() as u8) - 1);
+      return Ok(Some(parse::digit(line, index, 0, n as u32 - 1)?));
     }
 }
 
-pub fn row<T>(grid: &Vec<Vec<T>>, index: usize) -> Vec<&T> {
-    return grid[index].iter().map(|x| x).collect();
-}
-
-pub fn column<T>(grid: &Vec<Vec<T>>, index: usize) -> Vec<&T> {
-    return grid.iter().map(|x| &x[index]).collect();
-}
-
 #[derive(Clone)]
 #[derive(Debug)]
+#[derive(PartialEq)]
 pub struct Puzzle {
     pub size: usize,
     pub grid: Vec<Vec<Option<u8>>>,
@@ -110,68 +115,93 @@ impl Puzzle {
         };
     }
 
-    pub fn from_human_string(s: &str) -> Puzzle {
-        let mut grid = Vec::new();
-
-        let mut iter = s.trim().split("\n").peekable();
+    // Reads the format written by to_human_string, so cell values are 0-indexed like in towers.
+    pub fn from_human_string(s: &str) -> Result<Puzzle, ParseError> {
+        let lines = parse::grid_lines(s);
+        let n = parse::square_size(&lines)?;
+        if n > MAX_SIZE {
+            return Err(lines[0].error(0, Reason::TooWide { size: n, max: MAX_SIZE }));
+        }
 
-        let mut row: Option<&str> = iter.next();
-        while row.is_some() {
-            let row_str = row.unwrap().trim();
+        let mut grid = Vec::new();
+        for line in lines.iter() {
             let mut grid_row = Vec::new();
-            for cell in row_str.chars() {
-                grid_row.push(char_to_cell(cell));
+            for j in 0..n {
+                grid_row.push(char_to_cell(line, j, n)?);
             }
             grid.push(grid_row);
-            row = iter.next();
         }
 
-        return Puzzle { size: grid.len(), grid, difficulty: 0 };
+        return Ok(Puzzle { size: n, grid, difficulty: 0 });
     }
 
-    pub fn from_tatham_string(s: &str) -> Puzzle {
-        let mut iter = s.trim().split(":");
-        let n = iter.next().unwrap().parse::<usize>().unwrap();
-        return Puzzle::from_tatham_string_with_size(n, iter.next().unwrap());
+    pub fn from_tatham_string(s: &str) -> Result<Puzzle, ParseError> {
+        let (n, rest, column) = parse::split_size(s, MAX_SIZE)?;
+        return Puzzle::from_tatham_string_with_size(n, rest).map_err(|e| e.shifted(column - 1));
     }
 
-    pub fn from_tatham_string_with_size(n:usize, s: &str) -> Puzzle {
-        let mut grid = Vec::new();
-
-        let mut grid_string = s.trim().chars().peekable();
-        let mut counter = 0;
-
-        for i in 0..n {
-            grid.push(Vec::new());
-            for j in 0..n {
-                if counter == 0 {
-                    let mut c = '_';
-                    while c == '_' {
-                        c = grid_string.next().unwrap();
-                    }
-                    if c.is_ascii_lowercase() {
-                        counter += (c as u8) - ('`' as u8);
-                    } else {
-                        let mut val = c.to_digit(10).unwrap();
-                        let mut p = grid_string.peek();
-                        while p.is_some() && p.unwrap().is_digit(10) {
-                            val *= 10;
-                            val += grid_string.next().unwrap().to_digit(10).unwrap();
-                            p = grid_string.peek();
-                        }
-                        grid[i].push(Option::Some((val as u8) - 1));
-                    }
+    pub fn from_tatham_string_with_size(n:usize, s: &str) -> Result<Puzzle, ParseError> {
+        let offset = s.chars().take_while(|c| c.is_whitespace()).count();
+        let error = |i: usize, reason: Reason| ParseError::new(1, offset + i + 1, reason);
+
+        let mut cells: Vec<Option<u8>> = Vec::new();
+        let trimmed: Vec<char> = s.trim().chars().collect();
+        let mut i = 0;
+        while i < trimmed.len() {
+            let c = trimmed[i];
+            if c == '_' {
+                i += 1;
+                continue;
+            }
+            if cells.len() == n*n {
+                return Err(error(i, Reason::TooLong));
+            }
+            if c.is_ascii_lowercase() {
+                let counter = ((c as u8) - b'`') as usize;
+                if cells.len() + counter > n*n {
+                    return Err(error(i, Reason::TooLong));
                 }
-                if counter > 0 {
-                    grid[i].push(Option::None);
-                    counter -= 1;
+                for _j in 0..counter {
+                    cells.push(Option::None);
                 }
+                i += 1;
+            } else if c.is_ascii_digit() {
+                let start = i;
+                let mut val: u32 = 0;
+                while i < trimmed.len() && trimmed[i].is_ascii_digit() {
+                    val = val.saturating_mul(10).saturating_add(trimmed[i].to_digit(10).unwrap());
+                    i += 1;
+                }
+                if val < 1 || val > n as u32 {
+                    return Err(error(start, Reason::ValueOutOfRange { value: val, min: 1, max: n as u32 }));
+                }
+                cells.push(Option::Some((val as u8) - 1));
+            } else {
+                return Err(error(i, Reason::BadCharacter(c)));
             }
         }
-        return Puzzle { size: n, grid, difficulty: 0 };
+        if cells.len() < n*n {
+            return Err(error(trimmed.len(), Reason::Truncated));
+        }
+
+        let grid = cells.chunks(n).map(|row| row.to_vec()).collect();
+        return Ok(Puzzle { size: n, grid, difficulty: 0 });
     }
+}
 
-    pub fn number_of_hints(&self) -> usize {
+impl puzzle::Puzzle for Puzzle {
+    // The solution's cells are 0-indexed, like the values in the puzzle's grid.
+    type Solution = Vec<Vec<u8>>;
+
+    fn size(&self) -> usize {
+        return self.size;
+    }
+
+    fn difficulty(&self) -> u8 {
+        return self.difficulty;
+    }
+
+    fn number_of_hints(&self) -> usize {
         let mut total = 0;
 
         for row in &self.grid {
@@ -185,7 +215,7 @@ impl Puzzle {
         return total;
     }
 
-    pub fn with_hints_removed(&self, hints_to_remove: &Vec<bool>, difficulty: u8) -> Puzzle {
+    fn with_hints_removed(&self, hints_to_remove: &[bool], difficulty: u8) -> Puzzle {
         let n = self.grid.len();
         let mut total = 0;
 
@@ -205,4 +235,21 @@ impl Puzzle {
             size: grid.len(), grid, difficulty,
         };
     }
+
+    // Latin puzzles can either be given as a Tatham game ID or as a grid.
+    fn from_string(s: &str) -> Result<Puzzle, ParseError> {
+        if s.contains(':') {
+            return Puzzle::from_tatham_string(s);
+        } else {
+            return Puzzle::from_human_string(s);
+        }
+    }
+
+    fn to_string(&self) -> String {
+        return self.to_tatham_string();
+    }
+
+    fn is_solution(&self, solution: &Vec<Vec<u8>>) -> bool {
+        return verify::verify(self, solution).is_ok();
+    }
 }
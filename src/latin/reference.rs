@@ -0,0 +1,40 @@
+// A plain backtracking solver to check the real one against. It tries every value of every cell in
+// row major order, so it's only usable on small puzzles, but it has no inference rules that could
+// be wrong.
+
+use super::puzzle::Puzzle;
+
+// The solutions of the puzzle, stopping once limit of them are found.
+pub fn solutions(puzzle: &Puzzle, limit: usize) -> Vec<Vec<Vec<u8>>> {
+    let n = puzzle.size;
+    let mut grid = vec![vec![0; n]; n];
+    let mut found = Vec::new();
+    fill(puzzle, &mut grid, 0, limit, &mut found);
+    return found;
+}
+
+// Tries every value of the cell that keeps the rows and columns filled so far latin, then fills the
+// cells after it.
+fn fill(puzzle: &Puzzle, grid: &mut Vec<Vec<u8>>, cell: usize, limit: usize, found: &mut Vec<Vec<Vec<u8>>>) {
+    let n = puzzle.size;
+    if found.len() >= limit {
+        return;
+    }
+    if cell == n * n {
+        found.push(grid.clone());
+        return;
+    }
+    let (i, j) = (cell / n, cell % n);
+    for value in 0..n as u8 {
+        if let Some(hint) = puzzle.grid[i][j] {
+            if hint != value {
+                continue;
+            }
+        }
+        if (0..j).any(|k| grid[i][k] == value) || (0..i).any(|k| grid[k][j] == value) {
+            continue;
+        }
+        grid[i][j] = value;
+        fill(puzzle, grid, cell + 1, limit, found);
+    }
+}
@@ -1,31 +1,29 @@
 pub mod triple;
+pub mod deduction;
 mod depth_solver;
 mod implication_solver;
+mod lines;
 
 use super::puzzle::Puzzle;
+use crate::bitset::BitSet;
+use crate::grader;
+use crate::grader::Grade;
+use crate::solver::PuzzleSolver;
+use crate::solver::Search;
+pub use crate::solver::Status;
 use triple::*;
+use deduction::*;
 use implication_solver::*;
+use lines::*;
+pub use implication_solver::BinaryTriple;
 
 use std::time::Instant;
-use std::collections::HashSet;
 use std::collections::HashMap;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Status {
-    // No solution exist
-    Unsolvable,
-    // Only one solution exists
-    UniqueSolution,
-    // Many solutions exist
-    MultipleSolutions,
-    // Don't know if solvable or not yet
-    InProgress,
-}
-
-fn possibilities_to_detailed_string(p: &HashSet<u8>, size: usize) -> String {
+fn possibilities_to_detailed_string(p: u64, size: usize) -> String {
     let mut result = String::with_capacity(size);
     for i in 0..size {
-        if p.contains(&(i as u8)) {
+        if p & (1 << i) != 0 {
             result.push_str(&(i+1).to_string());
         } else {
             result.push_str("_");
@@ -34,22 +32,44 @@ fn possibilities_to_detailed_string(p: &HashSet<u8>, size: usize) -> String {
     return result;
 }
 
+// The filled in grid, if every cell of the candidate grid is down to a single value.
+pub fn solution_from_candidates(candidates: &Vec<Vec<Vec<u8>>>) -> Option<Vec<Vec<u8>>> {
+    let mut grid = Vec::new();
+    for row in candidates {
+        let mut grid_row = Vec::new();
+        for values in row {
+            if values.len() != 1 {
+                return None;
+            }
+            grid_row.push(values[0]);
+        }
+        grid.push(grid_row);
+    }
+    return Some(grid);
+}
+
 #[derive(Clone, Debug)]
 pub struct Solver {
     pub puzzle: Puzzle,
-    pub all_triples: HashSet<Triple>,
-    pub all_set: HashSet<Triple>,
-    pub row_col_map: HashMap<RowCol, HashSet<u8>>,
-    pub row_val_map: HashMap<RowVal, HashSet<u8>>,
-    pub col_val_map: HashMap<ColVal, HashSet<u8>>,
+    // The triples still possible and the triples set, indexed by Triple::index.
+    pub all_triples: BitSet,
+    pub all_set: BitSet,
+    pub lines: Lines,
 
-    pub to_set: HashSet<Triple>,
-    pub to_remove: HashSet<Triple>,
+    pub to_set: BitSet,
+    pub to_remove: BitSet,
 
     pub implication_tracker: ImplicationsTracker,
 
+    // Why each triple in to_set and to_remove is there.
+    pub reasons: HashMap<BinaryTriple, Deduction>,
+    // Everything the solver has learned so far, in the order it was applied.
+    pub log: Vec<Deduction>,
+
     pub status: Status,
     pub depth_needed: u8,
+    // Shared with the copies exploring guesses on other threads, if any.
+    pub search: Option<Search<Solver>>,
 }
 
 impl Solver {
@@ -60,8 +80,7 @@ impl Solver {
       for row in 0..n {
           let mut row_strings: Vec<String> = Vec::new();
           for col in 0..n {
-              let c = RowCol {row, col};
-              let p = self.row_col_map.get(&c).unwrap();
+              let p = self.lines.vals(&RowCol {row, col});
               row_strings.push(possibilities_to_detailed_string(p, n as usize));
           }
           rows.push(row_strings.join(" "));
@@ -73,91 +92,102 @@ impl Solver {
 
     pub fn new(p: Puzzle) -> Solver {
         let n = p.size;
-        let mut all_triples: HashSet<Triple> = HashSet::new();
-        let mut to_set: HashSet<Triple> = HashSet::new();
-        let mut row_col_map: HashMap<RowCol, HashSet<u8>> = HashMap::new();
-        let mut row_val_map: HashMap<RowVal, HashSet<u8>> = HashMap::new();
-        let mut col_val_map: HashMap<ColVal, HashSet<u8>> = HashMap::new();
-
-        for i in 0..n as u8 {
-            for j in 0..n as u8 {
-                row_col_map.insert(RowCol{row: i, col: j}, HashSet::new());
-                row_val_map.insert(RowVal{row: i, val: j}, HashSet::new());
-                col_val_map.insert(ColVal{col: i, val: j}, HashSet::new());
-            }
-        }
+        let count = n * n * n;
+        let mut givens: Vec<Triple> = Vec::new();
 
         for i in 0..n {
             for j in 0..n {
                 match p.grid[i][j] {
                     Some(x) => {
-                        to_set.insert(Triple{row: i as u8, col: j as u8, val: x});
+                        givens.push(Triple{row: i as u8, col: j as u8, val: x});
                     },
                     None => {},
                 }
-                for k in 0..n {
-                    let t = Triple{row: i as u8, col: j as u8, val: k as u8};
-                    all_triples.insert(t);
-                    row_col_map.get_mut(&t.get_row_col()).unwrap().insert(t.val);
-                    row_val_map.get_mut(&t.get_row_val()).unwrap().insert(t.col);
-                    col_val_map.get_mut(&t.get_col_val()).unwrap().insert(t.row);
-                }
             }
         }
 
-        return Solver {
+        let mut solver = Solver {
             puzzle: p,
-            all_triples,
-            all_set: HashSet::new(),
-            row_col_map,
-            row_val_map,
-            col_val_map,
-            to_set,
-            to_remove: HashSet::new(),
+            all_triples: BitSet::full(count),
+            all_set: BitSet::new(count),
+            lines: Lines::new(n as u8),
+            to_set: BitSet::new(count),
+            to_remove: BitSet::new(count),
             implication_tracker: ImplicationsTracker::new(n as u8),
+            reasons: HashMap::new(),
+            log: Vec::new(),
             status: Status::InProgress,
             depth_needed: 0,
+            search: None,
+        };
+        for t in givens {
+            solver.queue(BinaryTriple { t, negated: false }, Rule::Given, Vec::new);
+        }
+        return solver;
+    }
+
+    // The values each cell can still take, in increasing order.
+    // Like in the puzzle's grid, values are 0-indexed.
+    pub fn candidates(&self) -> Vec<Vec<Vec<u8>>> {
+        let n = self.puzzle.size as u8;
+        let mut grid = Vec::new();
+        for row in 0..n {
+            let mut grid_row = Vec::new();
+            for col in 0..n {
+                grid_row.push(members(self.lines.vals(&RowCol {row, col})).collect());
+            }
+            grid.push(grid_row);
         }
+        return grid;
+    }
+
+    // The filled in grid, if every cell is down to a single value.
+    pub fn solution(&self) -> Option<Vec<Vec<u8>>> {
+        if self.status == Status::Unsolvable {
+            return None;
+        }
+        return solution_from_candidates(&self.candidates());
     }
 
     pub fn remove(&mut self, t: &Triple) {
-        if self.all_triples.remove(&t) {
-            let mut set;
+        let n = self.puzzle.size as u8;
+        if self.all_triples.remove(t.index(n)) {
+            self.lines.remove(t);
 
-            set = self.row_col_map.get_mut(&t.get_row_col()).unwrap();
-            set.remove(&t.val);
-            match set.len() {
+            let set = self.lines.vals(&t.get_row_col());
+            match set.count_ones() {
                 0 => { self.status = Status::Unsolvable; },
                 1 => {
-                    let val = set.iter().next().unwrap().clone();
-                    self.to_set.insert(t.with_val(val));
+                    let single = t.with_val(set.trailing_zeros() as u8);
+                    let fact = BinaryTriple { t: single, negated: false };
+                    self.queue(fact, Rule::NakedSingle, || naked_single_premises(&single, n));
                 },
                 _ => {},
             }
 
-            set = self.row_val_map.get_mut(&t.get_row_val()).unwrap();
-            set.remove(&t.col);
-            match set.len() {
+            let set = self.lines.cols(&t.get_row_val());
+            match set.count_ones() {
                 0 => { self.status = Status::Unsolvable; },
                 1 => {
-                    let col = set.iter().next().unwrap().clone();
-                    self.to_set.insert(t.with_col(col));
+                    let single = t.with_col(set.trailing_zeros() as u8);
+                    let fact = BinaryTriple { t: single, negated: false };
+                    self.queue(fact, Rule::HiddenSingleInRow, || row_single_premises(&single, n));
                 },
                 _ => {},
             }
 
-            set = self.col_val_map.get_mut(&t.get_col_val()).unwrap();
-            set.remove(&t.row);
-            match set.len() {
+            let set = self.lines.rows(&t.get_col_val());
+            match set.count_ones() {
                 0 => { self.status = Status::Unsolvable; },
                 1 => {
-                    let row = set.iter().next().unwrap().clone();
-                    self.to_set.insert(t.with_row(row));
+                    let single = t.with_row(set.trailing_zeros() as u8);
+                    let fact = BinaryTriple { t: single, negated: false };
+                    self.queue(fact, Rule::HiddenSingleInColumn, || column_single_premises(&single, n));
                 },
                 _ => {},
             }
 
-            let n = self.puzzle.size;
+            let n = n as usize;
             if self.status == Status::InProgress && self.all_triples.len() == n * n {
                 self.status = Status::UniqueSolution;
             }
@@ -166,61 +196,100 @@ impl Solver {
 
     pub fn remove_conflict_with_set(&mut self, t: &Triple) {
         let n = self.puzzle.size as u8;
+        let premise = BinaryTriple { t: *t, negated: false };
         for i in 0..n {
+            let mut conflicts = Vec::new();
             if t.val != i {
-                let o = Triple{row: t.row, col: t.col, val: i};
-                if self.all_triples.contains(&o) {
-                    self.to_remove.insert(o);
-                }
+                conflicts.push(Triple{row: t.row, col: t.col, val: i});
             }
             if t.col != i {
-                let o = Triple{row: t.row, col: i, val: t.val};
-                if self.all_triples.contains(&o) {
-                    self.to_remove.insert(o);
-                }
+                conflicts.push(Triple{row: t.row, col: i, val: t.val});
             }
             if t.row != i {
-                let o = Triple{row: i, col: t.col, val: t.val};
-                if self.all_triples.contains(&o) {
-                    self.to_remove.insert(o);
-                }
+                conflicts.push(Triple{row: i, col: t.col, val: t.val});
+            }
+            for o in conflicts {
+                self.queue(BinaryTriple { t: o, negated: true }, Rule::Elimination, || vec![premise]);
             }
         }
     }
 
+    // Adds fact to to_set or to_remove unless it is already known, remembering the easiest reason
+    // given for it. premises is only called if the reason is kept.
+    pub fn queue<F>(&mut self, fact: BinaryTriple, rule: Rule, premises: F)
+        where F: FnOnce() -> Vec<BinaryTriple> {
+        let i = fact.t.index(self.puzzle.size as u8);
+        if fact.negated {
+            if !self.all_triples.contains(i) {
+                return;
+            }
+            self.to_remove.insert(i);
+        } else {
+            if self.all_set.contains(i) {
+                return;
+            }
+            self.to_set.insert(i);
+        }
+        match self.reasons.get(&fact) {
+            Some(d) if d.rule <= rule => {},
+            _ => { self.reasons.insert(fact, Deduction { fact, rule, premises: premises() }); },
+        }
+    }
+
+    // Takes the queued triples out of queue, easiest deductions first.
+    fn drain_queue(&mut self, negated: bool) -> Vec<Deduction> {
+        let n = self.puzzle.size as u8;
+        let queue = if negated { &mut self.to_remove } else { &mut self.to_set };
+        let drained: Vec<usize> = queue.iter().collect();
+        queue.clear();
+        let mut deductions: Vec<Deduction> = Vec::new();
+        for i in drained {
+            let fact = BinaryTriple { t: Triple::from_index(i, n), negated };
+            deductions.push(match self.reasons.remove(&fact) {
+                Some(d) => d,
+                None => Deduction { fact, rule: Rule::Implication, premises: vec![] },
+            });
+        }
+        deductions.sort_by_key(|d| (d.rule, d.fact.t));
+        return deductions;
+    }
+
     fn process_to_set(&mut self) {
-        let to_set:HashSet<Triple> = self.to_set.drain().collect();
-        self.all_set = &self.all_set | &to_set;
-        for t in to_set {
-            let result = self.implication_tracker.set_triple(&t);
-            self.sort_binary_triples(result);
+        let to_set = self.drain_queue(false);
+        let mut new = Vec::new();
+        let n = self.puzzle.size as u8;
+        for d in to_set {
+            if self.all_set.insert(d.fact.t.index(n)) {
+                new.push(d.fact.t);
+                self.log.push(d);
+            }
+        }
+        for t in new {
             self.remove_conflict_with_set(&t);
+            let result = self.implication_tracker.set_triple(&t);
+            self.sort_binary_triples(result, BinaryTriple { t, negated: false });
         }
     }
 
     fn process_to_remove(&mut self) {
-        let to_remove: HashSet<Triple> = self.to_remove.drain().collect();
-        for t in to_remove {
+        let to_remove = self.drain_queue(true);
+        let n = self.puzzle.size as u8;
+        for d in to_remove {
+            let t = d.fact.t;
+            if self.all_triples.contains(t.index(n)) {
+                self.log.push(d);
+            }
             let result = self.implication_tracker.remove_triple(&t);
-            self.sort_binary_triples(result);
+            self.sort_binary_triples(result, BinaryTriple { t, negated: true });
             self.remove(&t);
         }
     }
 
-    fn sort_binary_triples(&mut self, s: HashSet<BinaryTriple>) {
-        for t in s {
-            match t.negated {
-                false => {
-                    if !self.all_set.contains(&t.t) {
-                        self.to_set.insert(t.t.clone());
-                    }
-                },
-                true => {
-                    if self.all_triples.contains(&t.t) {
-                        self.to_remove.insert(t.t.clone()); 
-                    }
-                },
-            };
+    // Queues the facts that premise implies.
+    fn sort_binary_triples(&mut self, s: BitSet, premise: BinaryTriple) {
+        let n = self.puzzle.size as u8;
+        for t in s.iter().map(|i| BinaryTriple::from_index(i, n)) {
+            self.queue(t, Rule::Implication, || vec![premise]);
         }
     }
 
@@ -235,20 +304,14 @@ impl Solver {
                 self.implication_tracker.disjunctive_syllogism();
                 self.implication_tracker.hypothetical_syllogism();
 
-                for t in self.implication_tracker.get_contradictions() {
-                    if t.negated {
-                        if !self.all_set.contains(&t.t) {
-                            self.to_set.insert(t.t.clone());
-                        }
-                    } else {
-                        if self.all_triples.contains(&t.t) {
-                            self.to_remove.insert(t.t.clone());
-                        }
-                    }
+                for (t, clash) in self.implication_tracker.get_contradictions() {
+                    self.queue(t.opposite(), Rule::Contradiction, || vec![t, clash]);
                 }
                 if self.to_set.is_empty() && self.to_remove.is_empty() {
                     let result = self.implication_tracker.get_disjunction_elimination_inferences(self.puzzle.size as u8);
-                    self.sort_binary_triples(result);
+                    for (t, options) in result {
+                        self.queue(t, Rule::DisjunctionElimination, || options);
+                    }
                 }
             }
 
@@ -256,6 +319,41 @@ impl Solver {
         }
     }
 
+    // The easiest deduction that can be made next, or None if the solver can't make any progress
+    // without guessing, or if there's nothing left to find because the puzzle is already filled in
+    // or can't be solved.
+    // To get hints for a partly filled in puzzle, make a solver for the puzzle with the player's
+    // values as givens.
+    pub fn next_hint(&self) -> Option<Deduction> {
+        if self.puzzle.grid.iter().flatten().all(|x| x.is_some()) {
+            return None;
+        }
+        let mut copy = self.clone();
+        let start = copy.log.len();
+        copy.non_recursive_solve();
+        if copy.status == Status::Unsolvable {
+            return None;
+        }
+        let steps: Vec<Deduction> = copy.log.drain(start..).filter(|d| d.rule != Rule::Given).collect();
+        // Eliminations by values already placed are obvious to the player, so skip to the first step
+        // that isn't one.
+        return match steps.iter().find(|d| !d.fact.negated || d.rule != Rule::Elimination) {
+            Some(d) => Some(d.clone()),
+            None => steps.first().cloned(),
+        };
+    }
+
+    // Tells the other copies searching in parallel about the solutions found.
+    fn record_solutions(&self, solutions: &[Solver]) {
+        if let Some(search) = &self.search {
+            for s in solutions.iter().filter(|s| s.status == Status::UniqueSolution) {
+                let mut s = s.clone();
+                s.search = None;
+                search.record(s);
+            }
+        }
+    }
+
     pub fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Solver> {
         self.depth_needed = depth;
         let start = Instant::now();
@@ -268,6 +366,7 @@ impl Solver {
         } else if self.status == Status::UniqueSolution {
             solutions.push(self.clone());
         }
+        self.record_solutions(&solutions);
 
         let duration = start.elapsed();
         if should_log {
@@ -277,9 +376,48 @@ impl Solver {
             println!("{}Status: {:?}", indent, self.status);
             println!("{}Depth: {:?}", indent, depth);
             println!("{}Solutions #: {}", indent, solutions.len());
-            // println!("{}\n", self.inside_tracker.to_string());
         }
 
         return solutions;
     }
 }
+
+impl PuzzleSolver for Solver {
+    type Puzzle = Puzzle;
+
+    fn new(p: Puzzle) -> Solver {
+        return Solver::new(p);
+    }
+
+    fn non_recursive_solve(&mut self) {
+        Solver::non_recursive_solve(self);
+    }
+
+    fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Solver> {
+        return Solver::full_solve(self, depth, should_log);
+    }
+
+    fn search_in_parallel(&mut self, parallel_depth: u8) {
+        self.search = Some(Search::new(parallel_depth));
+    }
+
+    fn status(&self) -> Status {
+        return self.status;
+    }
+
+    fn depth_needed(&self) -> u8 {
+        return self.depth_needed;
+    }
+
+    fn grade(&self) -> Grade {
+        return grader::grade(self.log.iter().map(|d| d.rule));
+    }
+
+    fn solution(&self) -> Option<Vec<Vec<u8>>> {
+        return Solver::solution(self);
+    }
+
+    fn to_string(&self) -> String {
+        return Solver::to_string(self);
+    }
+}
@@ -0,0 +1,148 @@
+use super::triple::*;
+use super::BinaryTriple;
+use crate::grader::Technique;
+use crate::grader::Tier;
+
+// The ways the solver can learn something about a triple, from easiest to hardest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    // The value was given in the puzzle. No premises.
+    Given,
+    // The cell already has a value, or the value is already placed in the same row or column.
+    // Premise: the placed triple.
+    Elimination,
+    // Every other value of the cell was removed.
+    // Premises: the removed triples of the cell.
+    NakedSingle,
+    // Every other cell of the row was removed for this value.
+    // Premises: the removed triples of the row.
+    HiddenSingleInRow,
+    // Every other cell of the column was removed for this value.
+    // Premises: the removed triples of the column.
+    HiddenSingleInColumn,
+    // Follows from a chain of implications found earlier by hypothetical and disjunctive
+    // syllogism.
+    // Premise: the fact that was just learned and implies this one.
+    Implication,
+    // Assuming the opposite leads to a triple being both true and false.
+    // Premises: the assumption, then the triple that would be both true and false.
+    Contradiction,
+    // Every option left for a cell, or for a value in a row or column, implies this.
+    // Premises: the options.
+    DisjunctionElimination,
+    // A value tried by the depth solver to see where it leads.
+    // No premises.
+    Guess,
+    // A guess that led to no solution is removed.
+    // Premise: the guess.
+    TrialAndError,
+}
+
+impl Technique for Rule {
+    fn tier(&self) -> Tier {
+        return match self {
+            Rule::Given | Rule::Elimination | Rule::NakedSingle => Tier::Easy,
+            Rule::HiddenSingleInRow | Rule::HiddenSingleInColumn => Tier::Easy,
+            Rule::Implication => Tier::Normal,
+            Rule::Contradiction => Tier::Hard,
+            Rule::DisjunctionElimination => Tier::Extreme,
+            Rule::Guess | Rule::TrialAndError => Tier::Unreasonable,
+        };
+    }
+}
+
+// One thing the solver learned: a triple that was set (or removed if fact.negated), and why.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deduction {
+    pub fact: BinaryTriple,
+    pub rule: Rule,
+    pub premises: Vec<BinaryTriple>,
+}
+
+// "(2, 3) is 4" or "(2, 3) isn't 4", with 1-indexed rows, columns and values.
+fn describe(f: &BinaryTriple) -> String {
+    let verb = if f.negated { "isn't" } else { "is" };
+    return format!("({}, {}) {} {}", f.t.row + 1, f.t.col + 1, verb, f.t.val + 1);
+}
+
+fn describe_all(facts: &[BinaryTriple]) -> String {
+    return facts.iter().map(describe).collect::<Vec<String>>().join(", ");
+}
+
+// What the options of a disjunction elimination have in common.
+fn describe_options(options: &[BinaryTriple]) -> String {
+    let first = options[0].t;
+    if options.iter().all(|o| o.t.get_row_col() == first.get_row_col()) {
+        return format!("every value left for cell ({}, {})", first.row + 1, first.col + 1);
+    } else if options.iter().all(|o| o.t.get_row_val() == first.get_row_val()) {
+        return format!("every place left for {} in row {}", first.val + 1, first.row + 1);
+    } else {
+        return format!("every place left for {} in column {}", first.val + 1, first.col + 1);
+    }
+}
+
+impl Deduction {
+    // A sentence explaining the deduction to a player.
+    // Rows, columns and values are 1-indexed, like the player sees them.
+    pub fn explanation(&self) -> String {
+        let t = self.fact.t;
+        let conclusion = match self.fact.negated {
+            true => format!("Cell ({}, {}) can't be {}", t.row + 1, t.col + 1, t.val + 1),
+            false => format!("Cell ({}, {}) must be {}", t.row + 1, t.col + 1, t.val + 1),
+        };
+        return match self.rule {
+            Rule::Given => format!("Cell ({}, {}) is given as {}.", t.row + 1, t.col + 1, t.val + 1),
+            Rule::Elimination => {
+                let p = self.premises[0].t;
+                let place = if p.get_row_col() == t.get_row_col() {
+                    format!("it is already {}", p.val + 1)
+                } else if p.row == t.row {
+                    format!("row {} already has a {} in column {}", t.row + 1, t.val + 1, p.col + 1)
+                } else {
+                    format!("column {} already has a {} in row {}", t.col + 1, t.val + 1, p.row + 1)
+                };
+                format!("{}: {}.", conclusion, place)
+            },
+            Rule::NakedSingle => format!("{}: it is the only value left for that cell.", conclusion),
+            Rule::HiddenSingleInRow => {
+                format!("{}: it is the only place left for a {} in row {}.", conclusion, t.val + 1, t.row + 1)
+            },
+            Rule::HiddenSingleInColumn => {
+                format!("{}: it is the only place left for a {} in column {}.", conclusion, t.val + 1, t.col + 1)
+            },
+            Rule::Implication => {
+                format!("{}: it follows from {}.", conclusion, describe_all(&self.premises))
+            },
+            Rule::Contradiction => {
+                let clash = self.premises[1].t;
+                format!(
+                    "{}: if {}, then ({}, {}) would have to both be and not be {}.",
+                    conclusion, describe(&self.premises[0]), clash.row + 1, clash.col + 1, clash.val + 1)
+            },
+            Rule::DisjunctionElimination => {
+                format!(
+                    "{}: it follows from {} ({}).",
+                    conclusion, describe_options(&self.premises), describe_all(&self.premises))
+            },
+            Rule::Guess => format!("Guess that {}.", describe(&self.fact)),
+            Rule::TrialAndError => {
+                format!("{}: guessing it leads to a puzzle without solutions.", conclusion)
+            },
+        };
+    }
+}
+
+// The premises of a naked single: every other value of the cell is removed.
+pub fn naked_single_premises(t: &Triple, n: u8) -> Vec<BinaryTriple> {
+    return (0..n).filter(|v| *v != t.val).map(|v| BinaryTriple { t: t.with_val(v), negated: true }).collect();
+}
+
+// The premises of a hidden single in a row: every other column of the row is removed.
+pub fn row_single_premises(t: &Triple, n: u8) -> Vec<BinaryTriple> {
+    return (0..n).filter(|c| *c != t.col).map(|c| BinaryTriple { t: t.with_col(c), negated: true }).collect();
+}
+
+// The premises of a hidden single in a column: every other row of the column is removed.
+pub fn column_single_premises(t: &Triple, n: u8) -> Vec<BinaryTriple> {
+    return (0..n).filter(|r| *r != t.row).map(|r| BinaryTriple { t: t.with_row(r), negated: true }).collect();
+}
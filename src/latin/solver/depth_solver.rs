@@ -1,8 +1,10 @@
 use super::Solver;
 use super::Status;
+use super::BinaryTriple;
 use super::triple::*;
-
-use std::collections::HashSet;
+use super::deduction::Deduction;
+use super::deduction::Rule;
+use crate::solver;
 
 // Solver methods based on making a guess and seeing if we end up with a contradiction.
 impl Solver {
@@ -10,12 +12,20 @@ impl Solver {
         let n = self.puzzle.size as u8;
 
         while self.status == Status::InProgress {
+            if let Some(search) = &self.search {
+                if search.is_cancelled() {
+                    // Another copy found a second solution.
+                    self.status = Status::MultipleSolutions;
+                    return search.found();
+                }
+            }
+
             // Find a cell with multiple possibilities.
             let mut row_col: RowCol = RowCol{ row: 0, col: 0 };
-            let mut possibilities: &HashSet<u8>;
+            let mut possibilities: u64;
             loop {
-                possibilities = self.row_col_map.get(&row_col).unwrap();
-                if possibilities.len() > 1 {
+                possibilities = self.lines.vals(&row_col);
+                if possibilities.count_ones() > 1 {
                     break;
                 }
                 row_col.col += 1;
@@ -27,17 +37,24 @@ impl Solver {
 
             // Try setting a possibility.
             // Try the highest value first as they tend to give the most information.
-            let last = possibilities.iter().max().unwrap();
+            let last = 63 - possibilities.leading_zeros() as u8;
+
+            let guess = Triple{ row: row_col.row, col: row_col.col, val: last};
+            if self.search.as_ref().is_some_and(|s| s.should_fork(depth)) {
+                return self.depth_solve_in_parallel(guess, depth, should_log);
+            }
 
             let mut copy = self.clone();
-            let guess = Triple{ row: row_col.row, col: row_col.col, val: last.clone()};
             if should_log {
                 println!("\nStuck! Need to guess");
                 println!("{}", self.to_string());
                 println!("Guessing {}, in cell ({}, {})", guess.val + 1, guess.row + 1, guess.col + 1);
             }
-            copy.to_set.insert(guess.clone());
+            copy.queue(BinaryTriple { t: guess, negated: false }, Rule::Guess, Vec::new);
             let solutions = copy.full_solve(depth + 1, should_log);
+            if copy.depth_needed > self.depth_needed {
+                self.depth_needed = copy.depth_needed;
+            }
             if solutions.len() > 1 {
                 // If more than one solution with this guess, then we can stop looking.
                 self.status = Status::MultipleSolutions;
@@ -47,14 +64,19 @@ impl Solver {
                     println!("Guess leads to contradiciton; Reverting.");
                 }
                 // If no solutions with this guess, then we can remove this guess.
-                self.to_remove.insert(guess.clone());
+                let fact = BinaryTriple { t: guess, negated: true };
+                self.queue(fact, Rule::TrialAndError, || vec![fact.opposite()]);
                 // See if we can make more progress now that this guess is removed.
                 self.non_recursive_solve();
             } else {
                 // If exactly one solution with this guess, then we need to try it without the guess.
                 copy = self.clone();
-                copy.to_remove.insert(guess.clone());
+                let fact = BinaryTriple { t: guess, negated: true };
+                copy.queue(fact, Rule::Guess, Vec::new);
                 let other_solutions = copy.full_solve(depth + 1, should_log);
+                if copy.depth_needed > self.depth_needed {
+                    self.depth_needed = copy.depth_needed;
+                }
                 if other_solutions.is_empty() {
                     // If no solutions without this guess, then we had the unique solution with
                     // this guess.
@@ -74,4 +96,41 @@ impl Solver {
             return vec![self.clone()];
         }
     }
+
+    // Explores the guess and its opposite at the same time, each on its own thread.
+    fn depth_solve_in_parallel(&mut self, guess: Triple, depth: u8, should_log: bool) -> Vec<Solver> {
+        let mut with = self.clone();
+        with.queue(BinaryTriple { t: guess, negated: false }, Rule::Guess, Vec::new);
+        let fact = BinaryTriple { t: guess, negated: true };
+        let mut without = self.clone();
+        without.queue(fact, Rule::Guess, Vec::new);
+
+        let mut branches = [with, without];
+        let mut results = solver::solve_branches(&mut branches, depth + 1, should_log);
+        let needed = solver::depth_after_guess(branches[0].depth_needed, results[0].len(), branches[1].depth_needed);
+        if needed > self.depth_needed {
+            self.depth_needed = needed;
+        }
+        if results[0].is_empty() {
+            // depth_solve would have ruled the guess out by trial and error instead of guessing its
+            // opposite, so the solutions found without it log it the same way.
+            let start = self.log.len();
+            for s in results[1].iter_mut() {
+                s.log[start] = Deduction { fact, rule: Rule::TrialAndError, premises: vec![fact.opposite()] };
+            }
+        }
+
+        let search = self.search.as_ref().unwrap();
+        if search.is_cancelled() {
+            self.status = Status::MultipleSolutions;
+            return search.found();
+        }
+        let solutions = results.concat();
+        self.status = match solutions.len() {
+            0 => Status::Unsolvable,
+            1 => Status::UniqueSolution,
+            _ => Status::MultipleSolutions,
+        };
+        return solutions;
+    }
 }
@@ -1,8 +1,8 @@
 use super::triple::*;
+use super::lines::*;
+use crate::bitset::BitSet;
 
 use std::fmt;
-use std::collections::HashMap;
-use std::collections::HashSet;
 
 #[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
 pub struct BinaryTriple {
@@ -17,6 +17,16 @@ impl BinaryTriple {
             negated: !self.negated,
         };
     }
+
+    // The position of the fact among the 2 n^3 facts about a puzzle of size n. A triple and its
+    // negation are next to each other, so the opposite of index i is i ^ 1.
+    pub fn index(&self, n: u8) -> usize {
+        return 2 * self.t.index(n) + self.negated as usize;
+    }
+
+    pub fn from_index(i: usize, n: u8) -> BinaryTriple {
+        return BinaryTriple { t: Triple::from_index(i / 2, n), negated: i % 2 == 1 };
+    }
 }
 
 impl fmt::Display for BinaryTriple {
@@ -30,42 +40,21 @@ impl fmt::Display for BinaryTriple {
 
 #[derive(Debug, Clone)]
 pub struct ImplicationsTracker {
-    pub implications: HashMap<BinaryTriple, HashSet<BinaryTriple>>,
-    pub row_col_map: HashMap<RowCol, HashSet<u8>>,
-    pub row_val_map: HashMap<RowVal, HashSet<u8>>,
-    pub col_val_map: HashMap<ColVal, HashSet<u8>>,
+    pub size: u8,
+    // What each fact implies, itself included, indexed by BinaryTriple::index. None once the fact
+    // is known to be true or false, and for facts nothing was ever found to follow from.
+    pub implications: Vec<Option<BitSet>>,
+    // The triples not known to be true or false yet.
+    pub lines: Lines,
 }
 
 impl ImplicationsTracker {
     pub fn new(n: u8) -> Self {
-        let mut row_col_map: HashMap<RowCol, HashSet<u8>> = HashMap::new();
-        let mut row_val_map: HashMap<RowVal, HashSet<u8>> = HashMap::new();
-        let mut col_val_map: HashMap<ColVal, HashSet<u8>> = HashMap::new();
-
-        for i in 0..n as u8 {
-            for j in 0..n as u8 {
-                row_col_map.insert(RowCol{row: i, col: j}, HashSet::new());
-                row_val_map.insert(RowVal{row: i, val: j}, HashSet::new());
-                col_val_map.insert(ColVal{col: i, val: j}, HashSet::new());
-            }
-        }
-
-        for i in 0..n {
-            for j in 0..n {
-                for k in 0..n {
-                    let t = Triple{row: i as u8, col: j as u8, val: k as u8};
-                    row_col_map.get_mut(&t.get_row_col()).unwrap().insert(t.val);
-                    row_val_map.get_mut(&t.get_row_val()).unwrap().insert(t.col);
-                    col_val_map.get_mut(&t.get_col_val()).unwrap().insert(t.row);
-                }
-            }
-        }
-
+        let count = 2 * (n as usize).pow(3);
         let mut s = ImplicationsTracker {
-            implications: HashMap::new(),
-            row_col_map,
-            row_val_map,
-            col_val_map
+            size: n,
+            implications: vec![None; count],
+            lines: Lines::new(n),
         };
 
         for i in 0..n {
@@ -80,68 +69,69 @@ impl ImplicationsTracker {
         return s;
     }
 
-    pub fn set_triple(&mut self, t: &Triple) -> HashSet<BinaryTriple> {
+    fn fact_count(&self) -> usize {
+        return self.implications.len();
+    }
+
+    // The facts in s.
+    fn facts<'a>(&self, s: &'a BitSet) -> impl Iterator<Item = BinaryTriple> + 'a {
+        let n = self.size;
+        return s.iter().map(move |i| BinaryTriple::from_index(i, n));
+    }
+
+    pub fn set_triple(&mut self, t: &Triple) -> BitSet {
         return self.add_information(&BinaryTriple { t: t.clone(), negated: false });
     }
 
-    pub fn remove_triple(&mut self, t: &Triple) -> HashSet<BinaryTriple> {
+    pub fn remove_triple(&mut self, t: &Triple) -> BitSet {
         return self.add_information(&BinaryTriple { t: t.clone(), negated: true });
     }
 
-    fn remove(&mut self, t: &BinaryTriple) -> HashSet<BinaryTriple> {
+    fn remove(&mut self, t: &BinaryTriple) -> BitSet {
         if !t.negated {
-            self.row_col_map.get_mut(&t.t.get_row_col()).unwrap().remove(&t.t.val);
-            self.row_val_map.get_mut(&t.t.get_row_val()).unwrap().remove(&t.t.col);
-            self.col_val_map.get_mut(&t.t.get_col_val()).unwrap().remove(&t.t.row);
+            self.lines.remove(&t.t);
         }
-        return match self.implications.remove(t) {
+        return match self.implications[t.index(self.size)].take() {
             Some(x) => x,
-            None => HashSet::new(),
+            None => BitSet::new(self.fact_count()),
         };
     }
 
-    fn add_information(&mut self, t: &BinaryTriple) -> HashSet<BinaryTriple> {
-        // Fetch the implications and remove them from the hash map.
+    fn add_information(&mut self, t: &BinaryTriple) -> BitSet {
+        let i = t.index(self.size);
+        // Fetch the implications and remove them from the tracker.
         let implicated_triples = self.remove(t);
         // Remove the contrapositive implications as they are redundant.
         for implicated_triple in implicated_triples.iter() {
-            match self.implications.get_mut(&implicated_triple.opposite()) {
-                Some(x) => { x.remove(&t.opposite()); },
-                None => {},
-            };
+            if let Some(x) = &mut self.implications[implicated_triple ^ 1] {
+                x.remove(i ^ 1);
+            }
         }
         // Remove any implication that needed the oppositve of t as they won't ever be satisfied.
         let affected_triples = self.remove(&t.opposite());
         // Also remove the contrapositive implications.
         for affected_triple in affected_triples.iter() {
-            match self.implications.get_mut(&affected_triple.opposite()) {
-                Some(x) => { x.remove(t); }
-                None => {},
-            };
+            if let Some(x) = &mut self.implications[affected_triple ^ 1] {
+                x.remove(i);
+            }
         }
         return implicated_triples;
     }
 
     fn add_implication(&mut self, a: &BinaryTriple, c: &BinaryTriple) {
-        match self.implications.get_mut(a) {
-            Some(x) => { x.insert(c.clone()); },
-            None => {
-                let mut s = HashSet::new();
-                s.insert(a.clone());
-                s.insert(c.clone());
-                self.implications.insert(a.clone(), s);
-            },
-        };
+        let (a, c) = (a.index(self.size), c.index(self.size));
+        let count = self.fact_count();
+        self.implications[a].get_or_insert_with(|| {
+            let mut s = BitSet::new(count);
+            s.insert(a);
+            s
+        }).insert(c);
         // Also add the contrapositive.
-        match self.implications.get_mut(&c.opposite()) {
-            Some(x) => { x.insert(a.opposite()); },
-            None => {
-                let mut s = HashSet::new();
-                s.insert(c.opposite());
-                s.insert(a.opposite());
-                self.implications.insert(c.opposite(), s);
-            },
-        };
+        self.implications[c ^ 1].get_or_insert_with(|| {
+            let mut s = BitSet::new(count);
+            s.insert(c ^ 1);
+            s
+        }).insert(a ^ 1);
     }
 
     fn add_latin_square_implications(&mut self, t: &Triple, size: u8) {
@@ -166,22 +156,19 @@ impl ImplicationsTracker {
     // For example, if A -> B and B -> C, then add A -> C to the implications.
     pub fn hypothetical_syllogism(&mut self) {
         let copy = self.implications.clone();
-        for a in copy.keys() {
-            let all_implications = self.implications.get_mut(&a).unwrap();
+        let count = self.fact_count();
+        for all_implications in self.implications.iter_mut().flatten() {
             let mut new_implications = all_implications.clone();
             while !new_implications.is_empty() {
-                let drained: HashSet<BinaryTriple> = new_implications.drain().collect();
-                for c in drained {
-                    let c_implications = match copy.get(&c) {
-                        Some(x) => x,
-                        None => continue,
-                    };
-                    for new in c_implications {
-                        if all_implications.insert(new.clone()) {
-                            new_implications.insert(new.clone());
-                        }
+                let mut next = BitSet::new(count);
+                for c in new_implications.iter() {
+                    if let Some(x) = &copy[c] {
+                        next.union_with(x);
                     }
                 }
+                next.difference_with(all_implications);
+                all_implications.union_with(&next);
+                new_implications = next;
             }
         }
     }
@@ -189,109 +176,89 @@ impl ImplicationsTracker {
     // If T implies the negation of all but one value in a cell, then T implies that value.
     // Symetrically for row/val and col/val.
     pub fn disjunctive_syllogism(&mut self) -> bool {
+        let n = self.size;
         let mut did_add_something = false;
-        let copy = self.implications.clone();
-        for a in copy.keys() {
-            let implications = self.implications.get_mut(&a).unwrap();
-            let negations: HashSet<Triple> = implications.iter().filter(|x| x.negated).map(|x| x.t.clone()).collect();
-            for (row_col, mut vals) in self.row_col_map.clone() {
-                for n in negations.iter().filter(|x| x.get_row_col().eq(&row_col)) {
-                    vals.remove(&n.val);
-                }
-                if vals.len() == 1 {
-                    let val = vals.drain().next().unwrap();
-                    let t = Triple { row: row_col.row, col: row_col.col, val}; 
-                    did_add_something |= implications.insert(BinaryTriple{t, negated: false});
-                }
-            }
-
-            for (row_val, mut cols) in self.row_val_map.clone() {
-                for n in negations.iter().filter(|x| x.get_row_val().eq(&row_val)) {
-                    cols.remove(&n.col);
-                }
-                if cols.len() == 1 {
-                    let col = cols.drain().next().unwrap();
-                    let t = Triple { row: row_val.row, col, val: row_val.val}; 
-                    did_add_something |= implications.insert(BinaryTriple{t, negated: false});
-                }
+        for implications in self.implications.iter_mut().flatten() {
+            // What would be left if everything implied false was removed.
+            let mut left = self.lines.clone();
+            for i in implications.iter().filter(|i| i % 2 == 1) {
+                left.remove(&Triple::from_index(i / 2, n));
             }
-
-            for (col_val, mut rows) in self.col_val_map.clone() {
-                for n in negations.iter().filter(|x| x.get_col_val().eq(&col_val)) {
-                    rows.remove(&n.row);
-                }
-                if rows.len() == 1 {
-                    let row = rows.drain().next().unwrap();
-                    let t = Triple { row, col: col_val.col, val: col_val.val}; 
-                    did_add_something |= implications.insert(BinaryTriple{t, negated: false});
+            for a in 0..n {
+                for b in 0..n {
+                    let vals = left.vals(&RowCol { row: a, col: b });
+                    if vals.count_ones() == 1 {
+                        let t = Triple { row: a, col: b, val: vals.trailing_zeros() as u8 };
+                        did_add_something |= implications.insert(t.index(n) * 2);
+                    }
+                    let cols = left.cols(&RowVal { row: a, val: b });
+                    if cols.count_ones() == 1 {
+                        let t = Triple { row: a, col: cols.trailing_zeros() as u8, val: b };
+                        did_add_something |= implications.insert(t.index(n) * 2);
+                    }
+                    let rows = left.rows(&ColVal { col: a, val: b });
+                    if rows.count_ones() == 1 {
+                        let t = Triple { row: rows.trailing_zeros() as u8, col: a, val: b };
+                        did_add_something |= implications.insert(t.index(n) * 2);
+                    }
                 }
             }
         }
         return did_add_something;
     }
 
-    pub fn get_contradictions(&self) -> HashSet<BinaryTriple> {
-        let mut contradictions = HashSet::new();
-        for (key, val) in self.implications.iter() {
-            if val.contains(&key.opposite()) {
-                contradictions.insert(key.clone());
-            }
+    // The triples that imply their own opposite, each with a triple they imply to be both true and
+    // false.
+    pub fn get_contradictions(&self) -> Vec<(BinaryTriple, BinaryTriple)> {
+        let n = self.size;
+        let mut contradictions = Vec::new();
+        for (key, val) in self.implications.iter().enumerate() {
+            let val = match val {
+                Some(x) if x.contains(key ^ 1) => x,
+                _ => continue,
+            };
+            let key = BinaryTriple::from_index(key, n);
+            let clash = self.facts(val)
+                .filter(|x| !x.negated && val.contains(x.index(n) ^ 1))
+                .min_by_key(|x| (x.t != key.t, x.t))
+                .unwrap_or(key);
+            contradictions.push((key, clash));
         }
         return contradictions;
     }
 
-    pub fn get_disjunction_elimination_inferences(&self, size: u8) -> HashSet<BinaryTriple> {
-        let mut forced = HashSet::new();
+    // What is implied by every option left for a cell, or for a value in a row or column, each with
+    // the options it follows from.
+    pub fn get_disjunction_elimination_inferences(&self, size: u8) -> Vec<(BinaryTriple, Vec<BinaryTriple>)> {
+        let mut forced = Vec::new();
+        let mut seen = BitSet::new(self.fact_count());
         for i in 0..size {
             for j in 0..size {
-                let mut common_in_row_col: Option<HashSet<BinaryTriple>> = None;
-                let mut common_in_row_val: Option<HashSet<BinaryTriple>> = None;
-                let mut common_in_col_val: Option<HashSet<BinaryTriple>> = None;
-                for k in 0..size {
-                    let row_col_t = BinaryTriple { t: Triple{row: i, col: j, val: k }, negated: false };
-                    match self.implications.get(&row_col_t) {
-                        Some(x) => {
-                            common_in_row_col = match common_in_row_col {
-                                Some(y) => Some(&y & &x),
-                                None => Some(x.clone()),
-                            };
-                        },
-                        None => {},
-                    };
-
-                    let row_val_t = BinaryTriple { t: Triple{row: i, col: k, val: j }, negated: false };
-                    match self.implications.get(&row_val_t) {
-                        Some(x) => {
-                            common_in_row_val = match common_in_row_val {
-                                Some(y) => Some(&y & &x),
-                                None => Some(x.clone()),
+                let lines: [fn(u8, u8, u8) -> Triple; 3] = [
+                    |i, j, k| Triple { row: i, col: j, val: k },
+                    |i, j, k| Triple { row: i, col: k, val: j },
+                    |i, j, k| Triple { row: k, col: i, val: j },
+                ];
+                for line in lines {
+                    let mut common: Option<BitSet> = None;
+                    let mut options = Vec::new();
+                    for k in 0..size {
+                        let t = BinaryTriple { t: line(i, j, k), negated: false };
+                        if let Some(x) = &self.implications[t.index(size)] {
+                            options.push(t);
+                            match &mut common {
+                                Some(y) => y.intersect_with(x),
+                                None => common = Some(x.clone()),
                             };
-                        },
-                        None => {},
-                    };
-
-                    let col_val_t = BinaryTriple { t: Triple{row: k, col: i, val: j }, negated: false };
-                    match self.implications.get(&col_val_t) {
-                        Some(x) => {
-                            common_in_col_val = match common_in_col_val {
-                                Some(y) => Some(&y & &x),
-                                None => Some(x.clone()),
-                            };
-                        },
-                        None => {},
-                    };
-                }
-                match common_in_row_col {
-                    Some(x) => { forced = &forced | &x; },
-                    None => {},
-                }
-                match common_in_row_val {
-                    Some(x) => { forced = &forced | &x; },
-                    None => {},
-                }
-                match common_in_col_val {
-                    Some(x) => { forced = &forced | &x; },
-                    None => {},
+                        }
+                    }
+                    if let Some(x) = common {
+                        for f in x.iter() {
+                            if seen.insert(f) {
+                                forced.push((BinaryTriple::from_index(f, size), options.clone()));
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -302,9 +269,13 @@ impl ImplicationsTracker {
 
 impl fmt::Display for ImplicationsTracker {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (key, value) in self.implications.iter() {
-            write!(f, "{} => :\n\t", key)?;
-            for v in value {
+        for (key, value) in self.implications.iter().enumerate() {
+            let value = match value {
+                Some(x) => x,
+                None => continue,
+            };
+            write!(f, "{} => :\n\t", BinaryTriple::from_index(key, self.size))?;
+            for v in self.facts(value) {
                 write!(f, "{} ", v)?;
             }
             write!(f, "\n\n")?;
@@ -0,0 +1,55 @@
+use crate::latin::puzzle::MAX_SIZE;
+use super::triple::*;
+
+// The triples left, seen along the three kinds of line through the (row, col, val) cube: the values
+// left for each cell, the columns left for each value in each row, and the rows left for each value
+// in each column. Each line is a bit mask, so puzzles can be at most MAX_SIZE wide, which the
+// parsers and the command line check before a solver is made.
+#[derive(Clone, Debug)]
+pub struct Lines {
+    pub n: u8,
+    pub row_col: Vec<u64>,
+    pub row_val: Vec<u64>,
+    pub col_val: Vec<u64>,
+}
+
+impl Lines {
+    // Every triple of a puzzle of size n.
+    pub fn new(n: u8) -> Lines {
+        assert!(n as usize <= MAX_SIZE, "latin puzzles can be at most {} wide, not {}", MAX_SIZE, n);
+        let full = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+        let count = n as usize * n as usize;
+        return Lines { n, row_col: vec![full; count], row_val: vec![full; count], col_val: vec![full; count] };
+    }
+
+    fn at(&self, a: u8, b: u8) -> usize {
+        return a as usize * self.n as usize + b as usize;
+    }
+
+    pub fn remove(&mut self, t: &Triple) {
+        let (rc, rv, cv) = (self.at(t.row, t.col), self.at(t.row, t.val), self.at(t.col, t.val));
+        self.row_col[rc] &= !(1 << t.val);
+        self.row_val[rv] &= !(1 << t.col);
+        self.col_val[cv] &= !(1 << t.row);
+    }
+
+    // The values left for the cell.
+    pub fn vals(&self, c: &RowCol) -> u64 {
+        return self.row_col[self.at(c.row, c.col)];
+    }
+
+    // The columns of the row the value can still go in.
+    pub fn cols(&self, c: &RowVal) -> u64 {
+        return self.row_val[self.at(c.row, c.val)];
+    }
+
+    // The rows of the column the value can still go in.
+    pub fn rows(&self, c: &ColVal) -> u64 {
+        return self.col_val[self.at(c.col, c.val)];
+    }
+}
+
+// The members of a line's mask, in increasing order.
+pub fn members(mask: u64) -> impl Iterator<Item = u8> {
+    return (0..64).filter(move |i| mask & (1 << i) != 0);
+}
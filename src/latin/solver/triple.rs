@@ -1,6 +1,6 @@
 use std::fmt;
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Triple {
     pub row: u8,
     pub col: u8,
@@ -58,6 +58,18 @@ impl Triple {
     pub fn with_val(&self, val: u8) -> Triple {
         return Triple { row: self.row, col: self.col, val };
     }
+
+    // The position of the triple among the n^3 triples of a puzzle of size n, in the same order as
+    // Ord.
+    pub fn index(&self, n: u8) -> usize {
+        let n = n as usize;
+        return (self.row as usize * n + self.col as usize) * n + self.val as usize;
+    }
+
+    pub fn from_index(i: usize, n: u8) -> Triple {
+        let n = n as usize;
+        return Triple { row: (i / n / n) as u8, col: (i / n % n) as u8, val: (i % n) as u8 };
+    }
 }
 
 impl fmt::Display for Triple {
@@ -0,0 +1,112 @@
+// Uniformly random latin squares, using the Markov chain from Jacobson and Matthews, "Generating
+// uniformly distributed random latin squares" (1996).
+//
+// A latin square of order n is seen as an n x n x n incidence cube f, with f(r, c, s) = 1 when the
+// cell (r, c) holds the symbol s and 0 otherwise, so every line of the cube sums to 1. The chain
+// also visits "improper" cubes, where exactly one entry is -1. Each move adds 1 to f(r, c, s) for
+// some 0 or -1 entry and fixes up the lines through it, which keeps the lines summing to 1. Every
+// proper cube is equally likely in the chain's stationary distribution, so once it has mixed every
+// latin square is equally likely.
+
+use rand::Rng;
+
+struct Cube {
+    n: usize,
+    f: Vec<i8>,
+    // The entry that is -1, if the cube is improper.
+    improper: Option<(usize, usize, usize)>,
+}
+
+impl Cube {
+    // The cyclic square, cell (r, c) holds (r + c) mod n.
+    fn cyclic(n: usize) -> Cube {
+        let mut cube = Cube { n, f: vec![0; n * n * n], improper: None };
+        for r in 0..n {
+            for c in 0..n {
+                cube.add(r, c, (r + c) % n, 1);
+            }
+        }
+        return cube;
+    }
+
+    fn index(&self, r: usize, c: usize, s: usize) -> usize {
+        return (r * self.n + c) * self.n + s;
+    }
+
+    fn get(&self, r: usize, c: usize, s: usize) -> i8 {
+        return self.f[self.index(r, c, s)];
+    }
+
+    fn add(&mut self, r: usize, c: usize, s: usize, x: i8) {
+        let i = self.index(r, c, s);
+        self.f[i] += x;
+    }
+
+    // The indices i for which the entry of the line at i is 1. at(i) gives the entry's coordinates.
+    fn ones_on_line(&self, at: impl Fn(usize) -> (usize, usize, usize)) -> Vec<usize> {
+        return (0..self.n).filter(|i| {
+            let (r, c, s) = at(*i);
+            self.get(r, c, s) == 1
+        }).collect();
+    }
+
+    fn step<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let n = self.n;
+        let (r, c, s) = match self.improper {
+            Some(x) => x,
+            None => loop {
+                let (r, c, s) = (rng.gen_range(0..n), rng.gen_range(0..n), rng.gen_range(0..n));
+                if self.get(r, c, s) == 0 {
+                    break (r, c, s);
+                }
+            },
+        };
+
+        // A proper cube has exactly one 1 on each line, an improper one has two on the lines
+        // through its -1.
+        let rows = self.ones_on_line(|i| (i, c, s));
+        let columns = self.ones_on_line(|i| (r, i, s));
+        let symbols = self.ones_on_line(|i| (r, c, i));
+        let r2 = rows[rng.gen_range(0..rows.len())];
+        let c2 = columns[rng.gen_range(0..columns.len())];
+        let s2 = symbols[rng.gen_range(0..symbols.len())];
+
+        self.add(r, c, s, 1);
+        self.add(r, c2, s2, 1);
+        self.add(r2, c, s2, 1);
+        self.add(r2, c2, s, 1);
+        self.add(r, c, s2, -1);
+        self.add(r, c2, s, -1);
+        self.add(r2, c, s, -1);
+        self.add(r2, c2, s2, -1);
+
+        self.improper = if self.get(r2, c2, s2) < 0 { Some((r2, c2, s2)) } else { None };
+    }
+
+    fn rows(&self) -> Vec<Vec<u8>> {
+        let n = self.n;
+        return (0..n).map(|r| {
+            (0..n).map(|c| (0..n).find(|s| self.get(r, c, *s) == 1).unwrap() as u8).collect()
+        }).collect();
+    }
+}
+
+// A uniformly random latin square of order n, as rows of 0-indexed values.
+pub fn random_square<R: Rng + ?Sized>(n: u8, rng: &mut R) -> Vec<Vec<u8>> {
+    let n = n as usize;
+    let mut cube = Cube::cyclic(n);
+    if n < 2 {
+        return cube.rows();
+    }
+    // The proper cubes the chain passes through form a chain of their own, whose stationary
+    // distribution is uniform over latin squares. Its mixing time isn't known; this runs it for n^3
+    // steps, the heuristic Jacobson and Matthews suggest, so the square is only close to uniform.
+    let mut proper_visits = 0;
+    while proper_visits < n * n * n {
+        cube.step(rng);
+        if cube.improper.is_none() {
+            proper_visits += 1;
+        }
+    }
+    return cube.rows();
+}
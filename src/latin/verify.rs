@@ -0,0 +1,97 @@
+// Checking a filled in latin square against a puzzle without the solver, e.g. to grade a player's
+// answer, and saying which rule it breaks.
+
+use super::puzzle::Puzzle;
+
+use std::fmt;
+
+// The first rule a candidate solution was found to break.
+// Rows, columns and values are 0-indexed like in the puzzle's grid, but displayed 1-indexed like
+// the puzzle is.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    // The candidate doesn't have as many rows as the puzzle.
+    WrongRowCount { expected: usize, found: usize },
+    // A row of the candidate doesn't have as many cells as the puzzle.
+    WrongRowLength { row: usize, expected: usize, found: usize },
+    // A value that doesn't fit in the puzzle, like a 7 in a 5 by 5 latin square.
+    ValueOutOfRange { row: usize, column: usize, value: u8 },
+    // A cell that was given in the puzzle holds something else.
+    HintChanged { row: usize, column: usize, hint: u8, value: u8 },
+    // The value is in the row more than once, at least in the two columns.
+    RepeatedInRow { row: usize, value: u8, columns: (usize, usize) },
+    // The value is in the column more than once, at least in the two rows.
+    RepeatedInColumn { column: usize, value: u8, rows: (usize, usize) },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Violation::WrongRowCount { expected, found } => {
+                write!(f, "the grid has {} rows instead of {}", found, expected)
+            },
+            Violation::WrongRowLength { row, expected, found } => {
+                write!(f, "row {} has {} cells instead of {}", row + 1, found, expected)
+            },
+            Violation::ValueOutOfRange { row, column, value } => {
+                write!(f, "cell ({}, {}) holds {}, which is not in the puzzle", row + 1, column + 1, *value as usize + 1)
+            },
+            Violation::HintChanged { row, column, hint, value } => {
+                write!(f, "cell ({}, {}) holds {} instead of the given {}", row + 1, column + 1, value + 1, hint + 1)
+            },
+            Violation::RepeatedInRow { row, value, columns } => {
+                write!(f, "row {} has a {} in columns {} and {}", row + 1, value + 1, columns.0 + 1, columns.1 + 1)
+            },
+            Violation::RepeatedInColumn { column, value, rows } => {
+                write!(f, "column {} has a {} in rows {} and {}", column + 1, value + 1, rows.0 + 1, rows.1 + 1)
+            },
+        };
+    }
+}
+
+// Checks that the candidate is a latin square of the puzzle's size that keeps every given value.
+pub fn verify(puzzle: &Puzzle, candidate: &[Vec<u8>]) -> Result<(), Violation> {
+    let n = puzzle.size;
+    if candidate.len() != n {
+        return Err(Violation::WrongRowCount { expected: n, found: candidate.len() });
+    }
+    for (i, row) in candidate.iter().enumerate() {
+        if row.len() != n {
+            return Err(Violation::WrongRowLength { row: i, expected: n, found: row.len() });
+        }
+    }
+    for (i, row) in candidate.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            if value as usize >= n {
+                return Err(Violation::ValueOutOfRange { row: i, column: j, value });
+            }
+            if let Some(hint) = puzzle.grid[i][j] {
+                if hint != value {
+                    return Err(Violation::HintChanged { row: i, column: j, hint, value });
+                }
+            }
+        }
+    }
+    for (i, row) in candidate.iter().enumerate() {
+        // The column each value was first seen in.
+        let mut seen: Vec<Option<usize>> = vec![None; n];
+        for (j, &value) in row.iter().enumerate() {
+            if let Some(first) = seen[value as usize] {
+                return Err(Violation::RepeatedInRow { row: i, value, columns: (first, j) });
+            }
+            seen[value as usize] = Some(j);
+        }
+    }
+    for j in 0..n {
+        // The row each value was first seen in.
+        let mut seen: Vec<Option<usize>> = vec![None; n];
+        for (i, row) in candidate.iter().enumerate() {
+            let value = row[j];
+            if let Some(first) = seen[value as usize] {
+                return Err(Violation::RepeatedInColumn { column: j, value, rows: (first, i) });
+            }
+            seen[value as usize] = Some(i);
+        }
+    }
+    return Ok(());
+}
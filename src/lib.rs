@@ -0,0 +1,39 @@
+// The solvers' code predates running clippy on it, and keeps the style it was written in. These
+// are the lints that style sets off.
+#![allow(
+    clippy::assign_op_pattern,
+    clippy::bool_comparison,
+    clippy::char_lit_as_u8,
+    clippy::clone_on_copy,
+    clippy::collapsible_if,
+    clippy::collapsible_match,
+    clippy::for_kv_map,
+    clippy::inherent_to_string,
+    clippy::len_zero,
+    clippy::manual_swap,
+    clippy::manual_while_let_some,
+    clippy::map_clone,
+    clippy::needless_borrow,
+    clippy::needless_range_loop,
+    clippy::nonminimal_bool,
+    clippy::ptr_arg,
+    clippy::redundant_field_names,
+    clippy::single_char_add_str,
+    clippy::single_match,
+    clippy::unnecessary_cast,
+    clippy::unnecessary_unwrap,
+    clippy::upper_case_acronyms,
+    clippy::wrong_self_convention,
+)]
+
+pub mod bitset;
+pub mod corpus;
+pub mod grader;
+pub mod latin;
+pub mod loopy;
+pub mod maker;
+pub mod parse;
+pub mod perm;
+pub mod puzzle;
+pub mod solver;
+pub mod towers;
@@ -1,37 +1,40 @@
+use crate::grader::Tier;
+use crate::maker;
 use super::puzzle::Puzzle;
 use super::solver::Solver;
 use super::solver::coordinate::Coordinate;
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
+use std::ops::RangeInclusive;
 
 // (row, column, value)
 #[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
 pub struct Hint (usize, usize, u8);
 
-pub fn make_puzzle(size: usize) -> Puzzle {
+// A uniquely solvable puzzle made by adding random hints, and a random order to try removing its
+// hints in.
+fn hinted_with_removal_order<R: Rng + ?Sized>(width: usize, height: usize, rng: &mut R) -> (Puzzle, Vec<usize>) {
     let mut cells_with_hint: Vec<Coordinate> = Vec::new();
     let mut empty_cells: Vec<Coordinate> = Vec::new();
     let mut grid: Vec<Vec<Option<u8>>> = Vec::new();
-    for i in 0..size {
+    for i in 0..height {
         grid.push(Vec::new());
-        for j in 0..size {
+        for j in 0..width {
             grid[i].push(Option::None);
             empty_cells.push(Coordinate(i, j));
         }
     }
 
     // Add hints until uniquely solvable
-    let mut rng = thread_rng();
-    empty_cells.shuffle(&mut rng);
+    empty_cells.shuffle(rng);
 
     let mut next_coordinate = empty_cells.pop().unwrap();
     let mut possible_values = Vec::from([0, 1, 2, 3]);
-    possible_values.shuffle(&mut rng);
+    possible_values.shuffle(rng);
     let mut last_hint = (next_coordinate.0, next_coordinate.1, possible_values.pop().unwrap());
     grid[last_hint.0][last_hint.1] = Option::Some(last_hint.2);
     let mut solver = Solver::new(Puzzle::from_grid(&grid, 0));
-    println!("{}", solver.to_string());
     let mut solutions = solver.full_solve(0, false);
 
     while solutions.len() != 1 {
@@ -42,43 +45,40 @@ pub fn make_puzzle(size: usize) -> Puzzle {
                 None => {
                     // Reached a dead end.
                     // Try again lol
-                    println!("{}", solver.to_string());
-                    return make_puzzle(size);
+                    return hinted_with_removal_order(width, height, rng);
                 }
             }
             possible_values = Vec::from([0, 1, 2, 3]);
-            possible_values.shuffle(&mut rng);
+            possible_values.shuffle(rng);
         }
         match possible_values.pop() {
             Some(x) => { last_hint = (next_coordinate.0, next_coordinate.1, x); },
             None => {
-                println!("{}", solver.to_string());
-                println!("{:?}", last_hint);
                 last_hint = (next_coordinate.0, next_coordinate.1, 4);
             },
         }
         grid[last_hint.0][last_hint.1] = Option::Some(last_hint.2);
         solver = Solver::new(Puzzle::from_grid(&grid, 0));
-        println!("{}", solver.to_string());
         solutions = solver.full_solve(0, false);
     }
     cells_with_hint.push(next_coordinate);
 
     // Remove hints that keep it uniquely solvable
-    cells_with_hint.shuffle(&mut rng);
-    let mut difficulty = solver.depth_needed;
-    for cell in cells_with_hint {
-        let hint = grid[cell.0][cell.1].unwrap();
-        grid[cell.0][cell.1] = Option::None;
-        solver = Solver::new(Puzzle::from_grid(&grid, 0));
-        println!("{}", solver.to_string());
-        solutions = solver.full_solve(0, false);
-        if solutions.len() == 1 {
-            difficulty = solver.depth_needed;
-        } else {
-            grid[cell.0][cell.1] = Option::Some(hint);
-        }
-    }
+    let mut hints: Vec<usize> = (0..cells_with_hint.len()).collect();
+    hints.shuffle(rng);
+    return (Puzzle::from_grid(&grid, solver.depth_needed), hints);
+}
+
+pub fn make_puzzle<R: Rng + ?Sized>(width: usize, height: usize, rng: &mut R) -> Puzzle {
+    let (p, order) = hinted_with_removal_order(width, height, rng);
+    return maker::remove_hints::<Solver>(&p, &order);
+}
 
-    return Puzzle::from_grid(&grid, difficulty);
+// A puzzle whose grade is in tiers, or None if this attempt ended up too easy (or too hard: the
+// hints added to make the puzzle uniquely solvable can already be too hard).
+pub fn make_puzzle_in_tiers<R: Rng + ?Sized>(
+    width: usize, height: usize, tiers: &RangeInclusive<Tier>, rng: &mut R,
+) -> Option<Puzzle> {
+    let (p, order) = hinted_with_removal_order(width, height, rng);
+    return maker::remove_hints_in_tiers::<Solver>(&p, &order, tiers);
 }
@@ -1,33 +1,175 @@
-fn char_to_cell(c: char) -> Option<u8> {
+use crate::parse;
+use crate::parse::ParseError;
+use crate::parse::Reason;
+use crate::puzzle;
+use super::verify;
+
+fn char_to_cell(line: &parse::Line, index: usize) -> Result<Option<u8>, ParseError> {
+    let c = line.chars[index];
     if c == '.' || c == '·' {
-      return None;
+      return Ok(None);
     } else {
-      return Some(c.to_digit(10).unwrap() as u8);
+      return Ok(Some(parse::digit(line, index, 0, 4)?));
     }
 }
 
-pub fn row<T>(grid: &Vec<Vec<T>>, index: usize) -> Vec<&T> {
-    return grid[index].iter().map(|x| x).collect();
+// A loop drawn on the puzzle's grid.
+// horizontal[i][j] is whether the edge from node (i, j) to node (i, j + 1) is part of the loop and
+// vertical[i][j] is whether the edge from node (i, j) to node (i + 1, j) is.
+// Node (i, j) is the top left corner of cell (i, j).
+//
+// Solutions can be written in two formats.
+//
+// The text format (to_string/from_string) is the size of the grid as "WxH", then the H + 1 rows of
+// horizontal edges, then the H rows of vertical edges, one row per line, with 1 for an edge that
+// is part of the loop and 0 for one that isn't. For example, the loop around the top left cell of
+// a 2x1 grid is:
+//   2x1
+//   10
+//   10
+//   110
+//
+// The JSON format (to_json) has the width and height of the grid, the nodes of the loop in order
+// as [row, column] pairs (see loop_nodes) and the two edge matrices:
+//   {"width": 2, "height": 1, "loop": [[0, 0], [0, 1], [1, 1], [1, 0]],
+//    "horizontal": [[true, false], [true, false]], "vertical": [[true, true, false]]}
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+    pub horizontal: Vec<Vec<bool>>,
+    pub vertical: Vec<Vec<bool>>,
 }
 
-pub fn column<T>(grid: &Vec<Vec<T>>, index: usize) -> Vec<&T> {
-    return grid.iter().map(|x| &x[index]).collect();
+impl Solution {
+    pub fn width(&self) -> usize {
+        return if self.horizontal.is_empty() { 0 } else { self.horizontal[0].len() };
+    }
+
+    pub fn height(&self) -> usize {
+        return self.vertical.len();
+    }
+
+    // The nodes that can be reached from node (i, j) using an edge of the loop.
+    pub fn neighbours(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
+        let mut nodes = Vec::new();
+        if j < self.width() && self.horizontal[i][j] { nodes.push((i, j + 1)); }
+        if i < self.height() && self.vertical[i][j] { nodes.push((i + 1, j)); }
+        if j > 0 && self.horizontal[i][j - 1] { nodes.push((i, j - 1)); }
+        if i > 0 && self.vertical[i - 1][j] { nodes.push((i - 1, j)); }
+        return nodes;
+    }
+
+    // The nodes the loop goes through, in order, as (row, column) pairs.
+    // Starts at the top most, then left most, node of the loop and goes right from there.
+    // If the edges don't form a single loop, only the part connected to that first node is returned.
+    pub fn loop_nodes(&self) -> Vec<(usize, usize)> {
+        let mut nodes = Vec::new();
+        let start = (0..self.horizontal.len())
+            .flat_map(|i| (0..self.width()).map(move |j| (i, j)))
+            .find(|(i, j)| self.horizontal[*i][*j]);
+        let start = match start {
+            Some(x) => x,
+            None => { return nodes; },
+        };
+        // Since the start node is the top left most, its neighbours are to its right and below it.
+        // neighbours() lists the one to the right first.
+        let number_of_nodes = self.horizontal.len() * (self.width() + 1);
+        let mut previous = start;
+        let mut current = start;
+        while nodes.len() < number_of_nodes {
+            nodes.push(current);
+            match self.neighbours(current.0, current.1).into_iter().find(|x| *x != previous) {
+                Some(x) if x != start => {
+                    previous = current;
+                    current = x;
+                },
+                _ => { break; },
+            }
+        }
+        return nodes;
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut rows: Vec<String> = Vec::new();
+        rows.push(format!("{}x{}", self.width(), self.height()));
+        for row in self.horizontal.iter().chain(self.vertical.iter()) {
+            rows.push(row.iter().map(|e| if *e { '1' } else { '0' }).collect());
+        }
+        return rows.join("\n");
+    }
+
+    // Reads the format written by to_string.
+    pub fn from_string(s: &str) -> Result<Solution, ParseError> {
+        let lines = parse::grid_lines(s);
+        if lines.is_empty() {
+            return Err(ParseError::new(1, 1, Reason::MissingSize));
+        }
+        let size: String = lines[0].chars.iter().collect();
+        let (width, height) = match size.split_once("x") {
+            Some((w, h)) => {
+                let column = lines[0].first_column;
+                let width = parse::number(w, column, 1, 255)? as usize;
+                let height = parse::number(h, column + w.chars().count() + 1, 1, 255)? as usize;
+                (width, height)
+            },
+            None => { return Err(lines[0].error(0, Reason::MissingSize)); },
+        };
+        let rows = &lines[1..];
+        if rows.len() != 2 * height + 1 {
+            let line = if rows.len() > 2 * height + 1 { rows[2 * height + 1].number } else { lines[lines.len() - 1].number + 1 };
+            return Err(ParseError::new(line, 1, Reason::WrongHintCount { expected: 2 * height + 1, found: rows.len() }));
+        }
+
+        let mut horizontal = Vec::new();
+        let mut vertical = Vec::new();
+        for (k, line) in rows.iter().enumerate() {
+            let expected = if k <= height { width } else { width + 1 };
+            if line.chars.len() != expected {
+                let index = line.chars.len().min(expected);
+                return Err(line.error(index, Reason::WrongRowLength { expected, found: line.chars.len() }));
+            }
+            let mut row = Vec::new();
+            for j in 0..expected {
+                row.push(parse::digit(line, j, 0, 1)? == 1);
+            }
+            if k <= height {
+                horizontal.push(row);
+            } else {
+                vertical.push(row);
+            }
+        }
+        return Ok(Solution { horizontal, vertical });
+    }
+
+    pub fn to_json(&self) -> String {
+        let matrix = |m: &Vec<Vec<bool>>| {
+            let rows: Vec<String> = m.iter().map(|row| {
+                let edges: Vec<String> = row.iter().map(|e| e.to_string()).collect();
+                format!("[{}]", edges.join(", "))
+            }).collect();
+            format!("[{}]", rows.join(", "))
+        };
+        let nodes: Vec<String> = self.loop_nodes().iter().map(|(i, j)| format!("[{}, {}]", i, j)).collect();
+        return format!(
+            "{{\"width\": {}, \"height\": {}, \"loop\": [{}], \"horizontal\": {}, \"vertical\": {}}}",
+            self.width(), self.height(), nodes.join(", "), matrix(&self.horizontal), matrix(&self.vertical),
+        );
+    }
 }
 
 #[derive(Clone)]
 #[derive(Debug)]
 pub struct Puzzle {
-    pub size: usize,
+    pub width: usize,
+    pub height: usize,
     pub difficulty: u8,
     pub grid: Vec<Vec<Option<u8>>>,
 }
 
 impl Puzzle {
     pub fn to_string(&self) -> String {
-      let n = self.size;
       let mut rows: Vec<String> = Vec::new();
 
-      for i in 0..n {
+      for i in 0..self.height {
           let mut row = Vec::new();
           let content: Vec<String> = self.grid[i].iter().map(|hint| {
               match hint {
@@ -43,52 +185,166 @@ impl Puzzle {
       return rows.join("\n");
     }
 
+    // Writes the puzzle as a game ID for Simon Tatham's Loopy, like "4x4t0:a2b13e3a0b".
+    // Hints are listed row by row, with each run of up to 26 empty cells written as a letter (a for 1
+    // empty cell, b for 2, ...).
+    pub fn to_tatham_string(&self) -> String {
+        let mut char_list: Vec<String> = Vec::new();
+        let mut counter: u8 = 0;
+        for i in 0..self.height {
+            for j in 0..self.width {
+                match self.grid[i][j] {
+                    Some(x) => {
+                        if counter > 0 {
+                            char_list.push(String::from((b'`' + counter) as char));
+                            counter = 0;
+                        }
+                        char_list.push(x.to_string());
+                    },
+                    None => {
+                        counter += 1;
+                        if counter == 26 {
+                            char_list.push(String::from("z"));
+                            counter = 0;
+                        }
+                    },
+                };
+            }
+        }
+        if counter > 0 {
+            char_list.push(String::from((b'`' + counter) as char));
+        }
+        return [format!("{}x{}t0:", self.width, self.height), char_list.join("")].concat();
+    }
+
+    // Reads the format written by to_tatham_string.
+    // The parameters can also be just the size ("4:...") and can include Loopy's difficulty setting
+    // ("4x4t0de:..."), which is ignored. Only grids of squares ("t0") are supported.
+    pub fn from_tatham_string(s: &str) -> Result<Puzzle, ParseError> {
+        let offset = s.chars().take_while(|c| c.is_whitespace()).count();
+        let (params, desc) = match s.trim().split_once(":") {
+            Some(x) => x,
+            None => { return Err(ParseError::new(1, offset + 1, Reason::MissingSize)); },
+        };
+        let params: Vec<char> = params.chars().collect();
+        let error = |i: usize, reason: Reason| ParseError::new(1, offset + i + 1, reason);
+
+        // Width, then optionally "x" and height.
+        let mut i = 0;
+        let read_number = |i: &mut usize| {
+            let start = *i;
+            while *i < params.len() && params[*i].is_ascii_digit() {
+                *i += 1;
+            }
+            let digits: String = params[start..*i].iter().collect();
+            if digits.is_empty() {
+                return Err(error(start, Reason::MissingSize));
+            }
+            return parse::number(&digits, offset + start + 1, 1, 255).map(|x| x as usize);
+        };
+        let width = read_number(&mut i)?;
+        let mut height = width;
+        if i < params.len() && params[i] == 'x' {
+            i += 1;
+            height = read_number(&mut i)?;
+        }
+        if i < params.len() && params[i] == 't' {
+            i += 1;
+            if i >= params.len() || params[i] != '0' {
+                let c = if i < params.len() { params[i] } else { ':' };
+                return Err(error(i, Reason::BadCharacter(c)));
+            }
+            i += 1;
+        }
+        if i + 1 < params.len() && params[i] == 'd' {
+            i += 2;
+        }
+        if i < params.len() {
+            return Err(error(i, Reason::BadCharacter(params[i])));
+        }
+
+        let desc_column = offset + params.len() + 1;
+        let number_of_cells = width * height;
+        let mut cells: Vec<Option<u8>> = Vec::new();
+        for (k, c) in desc.chars().enumerate() {
+            let desc_error = |reason: Reason| ParseError::new(1, desc_column + k + 1, reason);
+            if cells.len() == number_of_cells {
+                return Err(desc_error(Reason::TooLong));
+            }
+            if c.is_ascii_lowercase() {
+                let counter = ((c as u8) - b'`') as usize;
+                if cells.len() + counter > number_of_cells {
+                    return Err(desc_error(Reason::TooLong));
+                }
+                for _j in 0..counter {
+                    cells.push(None);
+                }
+            } else if let Some(x) = c.to_digit(10) {
+                if x > 4 {
+                    return Err(desc_error(Reason::ValueOutOfRange { value: x, min: 0, max: 4 }));
+                }
+                cells.push(Some(x as u8));
+            } else {
+                return Err(desc_error(Reason::BadCharacter(c)));
+            }
+        }
+        if cells.len() < number_of_cells {
+            return Err(ParseError::new(1, desc_column + desc.chars().count() + 1, Reason::Truncated));
+        }
+
+        let grid = cells.chunks(width).map(|row| row.to_vec()).collect();
+        return Ok(Puzzle { width, height, difficulty: 0, grid });
+    }
+
     pub fn is_value(&self, value:u8, i: usize, j:usize) -> bool {
-        if !(i < self.size && j < self.size) {
+        if !(i < self.height && j < self.width) {
             return false;
         }
         let hint = self.grid[i][j];
         return hint.is_some() && hint.unwrap() == value;
     }
 
-    pub fn clone(&self) -> Puzzle {
-        return Puzzle {
-            size: self.grid.len(),
-            difficulty: self.difficulty,
-            grid: self.grid.clone(),
-        }
-    }
-
     pub fn from_grid(g: &Vec<Vec<Option<u8>>>, difficulty: u8) -> Puzzle {
+        let width = if g.is_empty() { 0 } else { g[0].len() };
         return Puzzle {
-            size: g.len(), difficulty, grid: g.clone(),
+            width, height: g.len(), difficulty, grid: g.clone(),
         };
     }
 
-    pub fn from_string(s: &str) -> Puzzle {
-        let mut grid = Vec::new();
-
-        let mut iter = s.trim().split("\n").peekable();
+    pub fn from_string(s: &str) -> Result<Puzzle, ParseError> {
+        let lines = parse::grid_lines(s);
+        let (width, height) = parse::rectangle_size(&lines)?;
 
-        let mut row: Option<&str> = iter.next();
-        while row.is_some() {
-            let row_str = row.unwrap().trim();
+        let mut grid = Vec::new();
+        for line in lines.iter() {
             let mut grid_row = Vec::new();
-            for cell in row_str.chars() {
-                grid_row.push(char_to_cell(cell));
+            for j in 0..width {
+                grid_row.push(char_to_cell(line, j)?);
             }
             grid.push(grid_row);
-            row = iter.next();
         }
 
-        return Puzzle {
-            size: grid.len(),
+        return Ok(Puzzle {
+            width,
+            height,
             difficulty: 0,
             grid: grid,
-        };
+        });
     }
+}
+
+impl puzzle::Puzzle for Puzzle {
+    type Solution = Solution;
 
-    pub fn number_of_hints(&self) -> usize {
+    fn size(&self) -> usize {
+        return self.width.max(self.height);
+    }
+
+    fn difficulty(&self) -> u8 {
+        return self.difficulty;
+    }
+
+    fn number_of_hints(&self) -> usize {
         let mut total = 0;
 
         for row in &self.grid {
@@ -102,16 +358,15 @@ impl Puzzle {
         return total;
     }
 
-    pub fn with_hints_removed(&self, hints_to_remove: &Vec<bool>, difficulty: u8) -> Puzzle {
-        let n = self.grid.len();
+    fn with_hints_removed(&self, hints_to_remove: &[bool], difficulty: u8) -> Puzzle {
         let mut total = 0;
 
         let mut grid = self.grid.clone();
-        for i in 0..n {
-            for j in 0..n {
-                if grid[i][j].is_some() {
+        for row in grid.iter_mut() {
+            for cell in row.iter_mut() {
+                if cell.is_some() {
                     if hints_to_remove[total] {
-                        grid[i][j] = None;
+                        *cell = None;
                     }
                     total += 1;
                 }
@@ -119,7 +374,24 @@ impl Puzzle {
         }
 
         return Puzzle {
-            size: grid.len(), difficulty, grid,
+            width: self.width, height: self.height, difficulty, grid,
         };
     }
+
+    // Loopy puzzles can either be given as a Tatham game ID or as a grid.
+    fn from_string(s: &str) -> Result<Puzzle, ParseError> {
+        if s.contains(':') {
+            return Puzzle::from_tatham_string(s);
+        } else {
+            return Puzzle::from_string(s);
+        }
+    }
+
+    fn to_string(&self) -> String {
+        return Puzzle::to_string(self);
+    }
+
+    fn is_solution(&self, solution: &Solution) -> bool {
+        return verify::verify(self, solution).is_ok();
+    }
 }
@@ -0,0 +1,142 @@
+// A plain backtracking solver to check the real one against. It turns every edge on or off in turn,
+// row by row, giving up on a branch as soon as a node or cell whose edges are all decided breaks a
+// rule, and checks that the edges form a single loop once they're all decided. It's only usable
+// on small puzzles, but it has no inference rules that could be wrong.
+
+use super::puzzle::Puzzle;
+use super::puzzle::Solution;
+use super::verify::verify;
+
+// An edge, as (row, column) in the horizontal or vertical edge matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edge {
+    Horizontal(usize, usize),
+    Vertical(usize, usize),
+}
+
+struct Search<'a> {
+    puzzle: &'a Puzzle,
+    // The edges in the order they're decided: the horizontal edges of a row of nodes, then the
+    // vertical edges below it.
+    order: Vec<Edge>,
+    edges: Solution,
+    limit: usize,
+    found: Vec<Solution>,
+}
+
+impl Search<'_> {
+    fn position(&self, e: Edge) -> usize {
+        let w = self.puzzle.width;
+        return match e {
+            Edge::Horizontal(i, j) => i * (2 * w + 1) + j,
+            Edge::Vertical(i, j) => i * (2 * w + 1) + w + j,
+        };
+    }
+
+    fn is_on(&self, e: Edge) -> bool {
+        return match e {
+            Edge::Horizontal(i, j) => self.edges.horizontal[i][j],
+            Edge::Vertical(i, j) => self.edges.vertical[i][j],
+        };
+    }
+
+    fn node_edges(&self, i: usize, j: usize) -> Vec<Edge> {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
+        let mut edges = Vec::new();
+        if j > 0 { edges.push(Edge::Horizontal(i, j - 1)); }
+        if j < w { edges.push(Edge::Horizontal(i, j)); }
+        if i > 0 { edges.push(Edge::Vertical(i - 1, j)); }
+        if i < h { edges.push(Edge::Vertical(i, j)); }
+        return edges;
+    }
+
+    fn cell_edges(&self, i: usize, j: usize) -> Vec<Edge> {
+        return Vec::from([Edge::Horizontal(i, j), Edge::Horizontal(i + 1, j), Edge::Vertical(i, j), Edge::Vertical(i, j + 1)]);
+    }
+
+    // Whether the edges around a node or cell can still end up with an allowed number of them on,
+    // once the edges up to position k are decided.
+    fn can_have(&self, edges: &[Edge], k: usize, allowed: &[usize]) -> bool {
+        let on = edges.iter().filter(|e| self.is_on(**e)).count();
+        if edges.iter().all(|e| self.position(*e) <= k) {
+            return allowed.contains(&on);
+        }
+        return allowed.iter().any(|x| *x >= on);
+    }
+
+    // Whether the nodes and cells around the edge at position k still respect the rules.
+    fn consistent(&self, k: usize) -> bool {
+        let mut nodes = Vec::new();
+        let mut cells = Vec::new();
+        match self.order[k] {
+            Edge::Horizontal(i, j) => {
+                nodes.push((i, j));
+                nodes.push((i, j + 1));
+                if i > 0 { cells.push((i - 1, j)); }
+                if i < self.puzzle.height { cells.push((i, j)); }
+            },
+            Edge::Vertical(i, j) => {
+                nodes.push((i, j));
+                nodes.push((i + 1, j));
+                if j > 0 { cells.push((i, j - 1)); }
+                if j < self.puzzle.width { cells.push((i, j)); }
+            },
+        }
+        for (i, j) in nodes {
+            if !self.can_have(&self.node_edges(i, j), k, &[0, 2]) {
+                return false;
+            }
+        }
+        for (i, j) in cells {
+            if let Some(hint) = self.puzzle.grid[i][j] {
+                if !self.can_have(&self.cell_edges(i, j), k, &[hint as usize]) {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    fn set(&mut self, e: Edge, on: bool) {
+        match e {
+            Edge::Horizontal(i, j) => { self.edges.horizontal[i][j] = on; },
+            Edge::Vertical(i, j) => { self.edges.vertical[i][j] = on; },
+        }
+    }
+
+    fn decide(&mut self, k: usize) {
+        if self.found.len() >= self.limit {
+            return;
+        }
+        if k == self.order.len() {
+            if verify(self.puzzle, &self.edges).is_ok() {
+                self.found.push(self.edges.clone());
+            }
+            return;
+        }
+        let e = self.order[k];
+        for on in [false, true] {
+            self.set(e, on);
+            if self.consistent(k) {
+                self.decide(k + 1);
+            }
+        }
+        self.set(e, false);
+    }
+}
+
+// The solutions of the puzzle, stopping once limit of them are found.
+pub fn solutions(puzzle: &Puzzle, limit: usize) -> Vec<Solution> {
+    let (h, w) = (puzzle.height, puzzle.width);
+    let mut order = Vec::new();
+    for i in 0..(h + 1) {
+        order.extend((0..w).map(|j| Edge::Horizontal(i, j)));
+        if i < h {
+            order.extend((0..(w + 1)).map(|j| Edge::Vertical(i, j)));
+        }
+    }
+    let edges = Solution { horizontal: vec![vec![false; w]; h + 1], vertical: vec![vec![false; w + 1]; h] };
+    let mut search = Search { puzzle, order, edges, limit, found: Vec::new() };
+    search.decide(0);
+    return search.found;
+}
@@ -1,4 +1,6 @@
 pub mod coordinate;
+pub mod edge;
+pub mod trace;
 mod inside_tracker;
 mod path_tracker;
 mod depth_solver;
@@ -6,32 +8,28 @@ mod corner_entry_solver;
 mod initial_solver;
 mod navigation;
 mod direction;
-mod edge;
 
 use coordinate::Coordinate;
 use direction::Direction;
 use direction::HDirection;
 use direction::VDirection;
 use edge::Edge;
-use edge::EdgeType;
 use inside_tracker::InsideTracker;
 use path_tracker::PathTracker;
+use trace::Premise;
+use trace::Rule;
+use trace::Step;
 use super::puzzle::Puzzle;
+use super::puzzle::Solution;
+use super::verify::verify;
+use crate::bitset::BitSet;
+use crate::grader;
+use crate::grader::Grade;
+use crate::solver::PuzzleSolver;
+use crate::solver::Search;
+pub use crate::solver::Status;
 
 use std::time::Instant;
-use std::collections::HashSet;
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Status {
-    // No solution exist
-    Unsolvable,
-    // Only one solution exists
-    UniqueSolution,
-    // Many solutions exist
-    MultipleSolutions,
-    // Don't know if solvable or not yet
-    InProgress,
-}
 
 fn cell_to_string(view: &Option<u8>) -> String {
     match view {
@@ -43,14 +41,17 @@ fn cell_to_string(view: &Option<u8>) -> String {
 #[derive(Clone, Debug)]
 pub struct Solver {
     puzzle: Puzzle,
-    remaining_edges: HashSet<Edge>,
-    remaining_edges_next_to_hints: HashSet<Edge>,
-    h_edges: Vec<Vec<Edge>>,
-    v_edges: Vec<Vec<Edge>>,
-    recently_affected_cells: HashSet<Coordinate>,
-    recently_affected_nodes: HashSet<Coordinate>,
-    recently_affected_corners: HashSet<Coordinate>,
-    paths_endpoints_to_check: HashSet<(Coordinate, Coordinate)>,
+    // The edges set on and off, indexed by edge_id. An edge in both is a contradiction.
+    on: BitSet,
+    off: BitSet,
+    remaining_edges: BitSet,
+    remaining_edges_next_to_hints: BitSet,
+    // Indexed by cell_id, node_id and node_id.
+    recently_affected_cells: BitSet,
+    recently_affected_nodes: BitSet,
+    recently_affected_corners: BitSet,
+    // Edges that join the two ends of a path.
+    loop_closing_edges_to_check: BitSet,
     corner_solver_data: corner_entry_solver::CornerSolverData,
     paths: PathTracker,
     inside_tracker: InsideTracker,
@@ -59,32 +60,36 @@ pub struct Solver {
     num_off: usize,
     pub status: Status,
     pub depth_needed: u8,
+    // Every edge decided so far, in order, with the rule that decided it.
+    pub trace: Vec<Step>,
+    // Shared with the copies exploring guesses on other threads, if any.
+    pub search: Option<Search<Solver>>,
 }
 
 impl Solver {
     pub fn to_string(&self) -> String {
-      let n = self.puzzle.size;
+      let (h, w) = (self.puzzle.height, self.puzzle.width);
       let mut rows: Vec<String> = Vec::new();
 
-      for i in 0..n {
+      for i in 0..h {
           let mut row = Vec::new();
-          for j in 0..n {
+          for j in 0..w {
               row.push(String::from(" "));
-              row.push(self.h_edges[i][j].to_string());
+              row.push(self.h_edge(i, j).to_string());
           }
           rows.push(row.join(""));
           row = Vec::new();
-          for j in 0..n {
-              row.push(self.v_edges[i][j].to_string());
+          for j in 0..w {
+              row.push(self.v_edge(i, j).to_string());
               row.push(cell_to_string(&self.puzzle.grid[i][j]));
           }
-          row.push(self.v_edges[i][n].to_string());
+          row.push(self.v_edge(i, w).to_string());
           rows.push(row.join(""));
       }
       let mut row = Vec::new();
-      for j in 0..n {
+      for j in 0..w {
           row.push(String::from(" "));
-          row.push(self.h_edges[n][j].to_string());
+          row.push(self.h_edge(h, j).to_string());
       }
       rows.push(row.join(""));
 
@@ -93,65 +98,58 @@ impl Solver {
     }
 
     pub fn new(p: Puzzle) -> Solver {
-        let n = p.size;
-        let mut h_edges: Vec<Vec<Edge>> = Vec::new();
-        let mut v_edges: Vec<Vec<Edge>> = Vec::new();
-        let mut remaining_edges:HashSet<Edge> = HashSet::new();
-        let mut remaining_edges_next_to_hints: HashSet<Edge> = HashSet::new();
-
-        for i in 0..n {
-            h_edges.push(Vec::new());
-            v_edges.push(Vec::new());
-            for j in 0..n {
-                let h_edge = Edge{is_on: false, is_off: false, row: i, col: j, edge_type: EdgeType::HORIZONTAL};
-                h_edges[i].push(h_edge.clone());
-                remaining_edges.insert(h_edge.clone());
-                if (i > 0 && p.grid[i-1][j].is_some()) || p.grid[i][j].is_some() {
-                    remaining_edges_next_to_hints.insert(h_edge.clone());
-                }
-                let v_edge = Edge{is_on: false, is_off: false, row: i, col: j, edge_type: EdgeType::VERTICAL};
-                v_edges[i].push(v_edge.clone());
-                remaining_edges.insert(v_edge.clone());
-                if (j > 0 && p.grid[i][j-1].is_some()) || p.grid[i][j].is_some() {
-                    remaining_edges_next_to_hints.insert(v_edge.clone());
-                }
-            }
-            let v_edge = Edge{is_on: false, is_off: false, row: i, col: n, edge_type: EdgeType::VERTICAL};
-            v_edges[i].push(v_edge);
-            remaining_edges.insert(v_edge.clone());
-            if p.grid[i][n-1].is_some() {
-                remaining_edges_next_to_hints.insert(v_edge.clone());
-            }
-        }
-        h_edges.push(Vec::new());
-        for j in 0..n {
-            let h_edge = Edge{is_on: false, is_off: false, row: n, col: j, edge_type: EdgeType::HORIZONTAL};
-            h_edges[n].push(h_edge.clone());
-            remaining_edges.insert(h_edge.clone());
-            if p.grid[n-1][j].is_some() {
-                remaining_edges_next_to_hints.insert(h_edge.clone());
-            }
-        }
-
-        return Solver {
+        let (h, w) = (p.height, p.width);
+        let num_edges = (h + 1) * w + h * (w + 1);
+        let num_nodes = (h + 1) * (w + 1);
+        let mut solver = Solver {
             puzzle: p,
-            h_edges,
-            v_edges,
-            remaining_edges,
-            remaining_edges_next_to_hints,
-            corner_solver_data: corner_entry_solver::CornerSolverData::new(),
-            inside_tracker: InsideTracker::new(n),
-            paths: PathTracker::new(),
+            on: BitSet::new(num_edges),
+            off: BitSet::new(num_edges),
+            remaining_edges: BitSet::full(num_edges),
+            remaining_edges_next_to_hints: BitSet::new(num_edges),
+            corner_solver_data: corner_entry_solver::CornerSolverData::new(num_nodes),
+            inside_tracker: InsideTracker::new(w, h),
+            paths: PathTracker::new(w, h),
             num_off: 0,
             can_be_single_cell: true,
             change_flag: false,
             status: Status::InProgress,
-            recently_affected_cells: HashSet::new(),
-            recently_affected_nodes: HashSet::new(),
-            recently_affected_corners: HashSet::new(),
-            paths_endpoints_to_check: HashSet::new(),
+            recently_affected_cells: BitSet::new(w * h),
+            recently_affected_nodes: BitSet::new(num_nodes),
+            recently_affected_corners: BitSet::new(num_nodes),
+            loop_closing_edges_to_check: BitSet::new(num_edges),
             depth_needed: 0,
+            trace: Vec::new(),
+            search: None,
+        };
+        for i in 0..h {
+            for j in 0..w {
+                if solver.puzzle.grid[i][j].is_some() {
+                    for e in solver.edges_from_cell(&Coordinate(i, j)) {
+                        let id = solver.edge_id(&e);
+                        solver.remaining_edges_next_to_hints.insert(id);
+                    }
+                }
+            }
         }
+        return solver;
+    }
+
+    // The loop this solver found, if it is a solution to the puzzle.
+    // Solvers returned by full_solve are always solutions.
+    pub fn solution(&self) -> Option<Solution> {
+        if self.status != Status::UniqueSolution {
+            return None;
+        }
+        return Some(self.edges_on());
+    }
+
+    // The edges that are on, as edge matrices.
+    fn edges_on(&self) -> Solution {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
+        let horizontal = (0..h + 1).map(|i| (0..w).map(|j| self.h_edge(i, j).is_on).collect()).collect();
+        let vertical = (0..h).map(|i| (0..w + 1).map(|j| self.v_edge(i, j).is_on).collect()).collect();
+        return Solution { horizontal, vertical };
     }
 
     fn is_value(&self, v: u8, i:usize, j:usize) -> bool {
@@ -167,26 +165,20 @@ impl Solver {
         return self.is_value(3, i, j);
     }
 
-    fn is_cell_value(&self, v: u8, c: &Coordinate) -> bool {
-        return self.puzzle.is_value(v, c.0, c.1);
-    }
-    fn is_cell_3(&self, c:&Coordinate) -> bool {
-        return self.is_cell_value(3, c);
-    }
-
     pub fn potential_degree(&self, c: &Coordinate) -> u8 {
         let mut count = 0;
-        for e in self.edges_from_node(c) {
-            if e.is_some() && !e.unwrap().is_off {
-                count += 1;
+        for d in Direction::iter() {
+            match self.edge_id_from_node(c, d) {
+                Some(id) if !self.off.contains(id) => { count += 1; },
+                _ => {},
             }
         }
         return count;
     }
 
     pub fn has_dead_end(&self) -> bool {
-        for row in 0..(self.puzzle.size + 1) {
-            for col in 0..(self.puzzle.size + 1) {
+        for row in 0..(self.puzzle.height + 1) {
+            for col in 0..(self.puzzle.width + 1) {
                 if self.potential_degree(&Coordinate(row, col)) == 1 {
                     return true;
                 }
@@ -195,28 +187,27 @@ impl Solver {
         return false;
     }
 
-    fn set(& mut self, edge: &Edge, on: bool) {
-        let nodes = self.nodes_from_edge(edge);
-        let actual_edge = match edge.edge_type {
-            EdgeType::HORIZONTAL => self.h_edges.get_mut(edge.row).unwrap().get_mut(edge.col).unwrap(),
-            EdgeType::VERTICAL => self.v_edges.get_mut(edge.row).unwrap().get_mut(edge.col).unwrap(),
-        };
+    fn set(& mut self, edge: &Edge, on: bool, rule: Rule, premises: &[Premise]) {
+        let id = self.edge_id(edge);
         let mut has_changed = false;
-        if on && !actual_edge.is_on {
-            actual_edge.is_on = true;
-            if actual_edge.is_off {
+        if on && !self.on.contains(id) {
+            self.on.insert(id);
+            if self.off.contains(id) {
                 self.status = Status::Unsolvable;
             }
+            let nodes = self.nodes_from_edge(edge);
             let endpoints = self.paths.add_edge(&nodes.0, &nodes.1);
-            match endpoints {
-                Some(x) => { self.paths_endpoints_to_check.insert(x); },
-                None => {},
-            };
+            if let Some(x) = endpoints.and_then(|x| self.edge_between(&x.0, &x.1)) {
+                self.loop_closing_edges_to_check.insert(self.edge_id(&x));
+            }
+            if self.paths.has_branch() {
+                self.status = Status::Unsolvable;
+            }
             has_changed = true;
         }
-        if !on && !actual_edge.is_off {
-            actual_edge.is_off = true;
-            if actual_edge.is_on {
+        if !on && !self.off.contains(id) {
+            self.off.insert(id);
+            if self.on.contains(id) {
                 self.status = Status::Unsolvable;
             }
             self.num_off += 1;
@@ -224,19 +215,20 @@ impl Solver {
         }
         if has_changed {
             self.change_flag = true;
-            self.inside_tracker.add_edge_info(&actual_edge);
+            self.inside_tracker.add_edge_info(&self.edge(id));
             let copy = Edge{is_on: false, is_off: false, row: edge.row, col: edge.col, edge_type: edge.edge_type};
-            self.remaining_edges.remove(&copy);
-            self.remaining_edges_next_to_hints.remove(&copy);
+            self.trace.push(Step { edge: copy, on, rule, premises: premises.to_vec() });
+            self.remaining_edges.remove(id);
+            self.remaining_edges_next_to_hints.remove(id);
             let new_cells = self.cells_from_edge(edge);
             for cell in new_cells {
                 match cell {
                     Some(x) => {
                         let corners = self.nodes_from_cell(&x);
-                        self.recently_affected_cells.insert(x);
+                        self.recently_affected_cells.insert(self.cell_id(&x));
                         for corner in corners {
-                            self.recently_affected_nodes.insert(corner);
-                            self.recently_affected_corners.insert(corner);
+                            self.recently_affected_nodes.insert(self.node_id(&corner));
+                            self.recently_affected_corners.insert(self.node_id(&corner));
                         }
                     },
                     None => {
@@ -248,46 +240,35 @@ impl Solver {
                 self.status = Status::Unsolvable;
             }
             for c in self.inside_tracker.get_neighbours_to_check() {
-                self.recently_affected_cells.insert(c);
-            }
-            let inferences = self.inside_tracker.get_inferences();
-            for e in inferences {
-                self.set(&e, e.is_on);
+                self.recently_affected_cells.insert(self.cell_id(&c));
             }
+            self.apply_inside_inferences(Rule::InsideOutside);
+        }
+    }
+
+    // Sets the edges the inside tracker inferred. The premises are the cells on either side of
+    // each edge.
+    fn apply_inside_inferences(&mut self, rule: Rule) {
+        let inferences = self.inside_tracker.get_inferences();
+        for e in inferences {
+            let premises: Vec<Premise> = self.cells_from_edge(&e).iter().flatten().map(|c| Premise::Cell(*c)).collect();
+            self.set(&e, e.is_on, rule, &premises);
         }
     }
 
     fn apply_local_single_loop_contraints(& mut self) {
-        let endpoints_list: Vec<(Coordinate, Coordinate)> = self.paths_endpoints_to_check.iter().cloned().collect();
-        for endpoints in endpoints_list {
+        let edges: Vec<usize> = self.loop_closing_edges_to_check.iter().collect();
+        for id in edges {
             if self.status != Status::InProgress {
                 return;
             }
             // If the endpoints of a path is an edge, then the path must be that edge or that edge
             // must off (otherwise it would close the loop and we would have more than two loops)
-            let edge: Edge;
-            if endpoints.0.0 == endpoints.1.0 {
-                if endpoints.0.1 + 1 == endpoints.1.1 {
-                    edge = self.edge_from_node(&endpoints.0, &Direction::RIGHT).unwrap();
-                } else if endpoints.0.1 == endpoints.1.1 + 1 {
-                    edge = self.edge_from_node(&endpoints.0, &Direction::LEFT).unwrap();
-                } else {
-                    continue;
-                }
-            } else if endpoints.0.1 == endpoints.1.1 {
-                if endpoints.0.0 + 1 == endpoints.1.0 {
-                    edge = self.edge_from_node(&endpoints.0, &Direction::DOWN).unwrap();
-                } else if endpoints.0.0 == endpoints.1.0 + 1 {
-                    edge = self.edge_from_node(&endpoints.0, &Direction::UP).unwrap();
-                } else {
-                    continue;
-                }
-            } else {
-                continue;
-            }
+            let edge = self.edge(id);
             if !edge.is_on && !edge.is_off {
                 if self.paths.num_paths() > 1 {
-                    self.set(&edge, false);
+                    let endpoints = self.nodes_from_edge(&edge);
+                    self.set(&edge, false, Rule::LoopClosing, &[Premise::Node(endpoints.0), Premise::Node(endpoints.1)]);
                 } else {
                     // There's a chance that this is the last edge missing.
                     // Try setting the edge and see if the contraints are satisfied.
@@ -300,12 +281,12 @@ impl Solver {
     }
 
     fn apply_node_constraints(& mut self) {
-        let nodes: Vec<Coordinate> = self.recently_affected_nodes.iter().cloned().collect();
+        let nodes: Vec<Coordinate> = self.recently_affected_nodes.iter().map(|i| self.node(i)).collect();
         for node in nodes {
             if self.status != Status::InProgress {
                 return;
             }
-            self.recently_affected_nodes.remove(&node);
+            self.recently_affected_nodes.remove(self.node_id(&node));
             let edges = self.edges_from_node(&node);
             let mut real_edges: Vec<Edge> = Vec::new();
             for e in edges {
@@ -315,6 +296,7 @@ impl Solver {
                 }
             }
 
+            let premises = [Premise::Node(node)];
             let mut on_count = 0;
             let mut off_count = 0;
 
@@ -328,7 +310,7 @@ impl Solver {
             } else if on_count == 2 {
                 // All other edges should be set to off.
                 for e in real_edges.iter() {
-                    if !e.is_on { self.set(&e, false); }
+                    if !e.is_on { self.set(e, false, Rule::NodeDegree, &premises); }
                 }
             } else if on_count == 1 {
                 // At least one other edge should be on.
@@ -338,7 +320,7 @@ impl Solver {
                 } else if on_count + off_count == real_edges.len() - 1 {
                     // If only one edge is not set, then that one should be set to on.
                     for e in real_edges.iter() {
-                        if !e.is_on && !e.is_off { self.set(&e, true); }
+                        if !e.is_on && !e.is_off { self.set(e, true, Rule::NodeDegree, &premises); }
                     }
                 }
                 // Otherwise, there is nothing we can do for now.
@@ -346,7 +328,7 @@ impl Solver {
                 // If only one edge is not set, then that one should be set to off.
                 if on_count + off_count == real_edges.len() - 1 {
                     for e in real_edges.iter() {
-                        if !e.is_on && !e.is_off { self.set(&e, false); }
+                        if !e.is_on && !e.is_off { self.set(e, false, Rule::NodeDegree, &premises); }
                     }
                 }
                 // Otherwise, there is nothing we can do for now.
@@ -355,17 +337,18 @@ impl Solver {
     }
 
     fn apply_cell_constraints(& mut self) {
-        let cells: Vec<Coordinate> = self.recently_affected_cells.iter().cloned().collect();
+        let cells: Vec<Coordinate> = self.recently_affected_cells.iter().map(|i| self.cell(i)).collect();
         for cell in cells {
             if self.status != Status::InProgress {
                 return;
             }
-            self.recently_affected_cells.remove(&cell);
+            self.recently_affected_cells.remove(self.cell_id(&cell));
             let hint = match self.puzzle.grid[cell.0][cell.1] {
                 Some(x) => { x },
                 None => { continue; },
             };
             let edges = self.edges_from_cell(&cell);
+            let premises = [Premise::Cell(cell)];
 
             let mut on_count = 0;
             let mut unknown_count = 0;
@@ -380,7 +363,7 @@ impl Solver {
             } else if on_count == hint {
                 // All unknown edges should be set to off.
                 for e in edges.iter() {
-                    if !e.is_on && !e.is_off { self.set(&e, false); }
+                    if !e.is_on && !e.is_off { self.set(e, false, Rule::CellCount, &premises); }
                 }
             }
 
@@ -389,7 +372,7 @@ impl Solver {
             } else if on_count + unknown_count == hint {
                 // All unknown edges should be set to on.
                 for e in edges {
-                    if !e.is_on && !e.is_off { self.set(&e, true); }
+                    if !e.is_on && !e.is_off { self.set(&e, true, Rule::CellCount, &premises); }
                 }
             }
 
@@ -428,11 +411,11 @@ impl Solver {
                 // Then we know that the corners opposite to the off edge will touch.
                 if edges[0].is_off {
                     // Top edge off
-                    if cell.0 < self.puzzle.size - 1 {
+                    if cell.0 < self.puzzle.height - 1 {
                         if cell.1 > 0 {
                             self.apply_corner_touch(&Coordinate(cell.0 + 1, cell.1 - 1), &HDirection::RIGHT, &VDirection::UP)
                         }
-                        if cell.1 < self.puzzle.size - 1 {
+                        if cell.1 < self.puzzle.width - 1 {
                             self.apply_corner_touch(&Coordinate(cell.0 + 1, cell.1 + 1), &HDirection::LEFT, &VDirection::UP)
                         }
                     }
@@ -443,18 +426,18 @@ impl Solver {
                         if cell.1 > 0 {
                             self.apply_corner_touch(&Coordinate(cell.0 - 1, cell.1 - 1), &HDirection::RIGHT, &VDirection::DOWN)
                         }
-                        if cell.1 < self.puzzle.size - 1 {
+                        if cell.1 < self.puzzle.width - 1 {
                             self.apply_corner_touch(&Coordinate(cell.0 - 1, cell.1 + 1), &HDirection::LEFT, &VDirection::DOWN)
                         }
                     }
                 }
                 if edges[3].is_off {
                     // Left edge off
-                    if cell.1 < self.puzzle.size - 1 {
+                    if cell.1 < self.puzzle.width - 1 {
                         if cell.0 > 0 {
                             self.apply_corner_touch(&Coordinate(cell.0 - 1, cell.1 + 1), &HDirection::LEFT, &VDirection::DOWN)
                         }
-                        if cell.0 < self.puzzle.size - 1 {
+                        if cell.0 < self.puzzle.height - 1 {
                             self.apply_corner_touch(&Coordinate(cell.0 + 1, cell.1 + 1), &HDirection::LEFT, &VDirection::UP)
                         }
                     }
@@ -465,7 +448,7 @@ impl Solver {
                         if cell.0 > 0 {
                             self.apply_corner_touch(&Coordinate(cell.0 - 1, cell.1 - 1), &HDirection::RIGHT, &VDirection::DOWN)
                         }
-                        if cell.0 < self.puzzle.size - 1 {
+                        if cell.0 < self.puzzle.height - 1 {
                             self.apply_corner_touch(&Coordinate(cell.0 + 1, cell.1 - 1), &HDirection::RIGHT, &VDirection::UP)
                         }
                     }
@@ -477,10 +460,7 @@ impl Solver {
             } else if hint == 3 {
                 self.inside_tracker.if_stuck_between_similar_make_different(&cell);
             }
-            let inferences = self.inside_tracker.get_inferences();
-            for e in inferences {
-                self.set(&e, e.is_on);
-            }
+            self.apply_inside_inferences(Rule::HintBetweenSimilarCells);
         }
     }
 
@@ -493,12 +473,13 @@ impl Solver {
         };
         let h_opposite_edge = self.edge_from_cell(cell, &hd.opposite().to_direction());
         let v_opposite_edge = self.edge_from_cell(cell, &vd.opposite().to_direction());
+        let premises = [Premise::Cell(*cell), Premise::Node(self.node_from_cell(cell, hd, vd))];
         if hint == 2 {
             if h_opposite_edge.is_off {
-                self.set(&v_opposite_edge, true);
+                self.set(&v_opposite_edge, true, Rule::CornerTouch, &premises);
             }
             if v_opposite_edge.is_off {
-                self.set(&h_opposite_edge, true);
+                self.set(&h_opposite_edge, true, Rule::CornerTouch, &premises);
             }
             // If the opposite corner is also touched, then we actually know that this opposite
             // touched corner must enter.
@@ -506,19 +487,19 @@ impl Solver {
             let is_on = |e: Option<Edge>| e.is_some() && e.unwrap().is_on;
             if is_on(self.edge_from_node(&opposite_corner, &hd.opposite().to_direction())) {
                 match self.edge_from_node(&opposite_corner, &vd.opposite().to_direction()) {
-                    Some(e) => { self.set(&e, false); },
+                    Some(e) => { self.set(&e, false, Rule::CornerTouch, &premises); },
                     None => {}
                 }
             }
             if is_on(self.edge_from_node(&opposite_corner, &vd.opposite().to_direction())) {
                 match self.edge_from_node(&opposite_corner, &hd.opposite().to_direction()) {
-                    Some(e) => { self.set(&e, false); },
+                    Some(e) => { self.set(&e, false, Rule::CornerTouch, &premises); },
                     None => {}
                 }
             }
         } else if hint == 3 {
-            self.set(&h_opposite_edge, true);
-            self.set(&v_opposite_edge, true);
+            self.set(&h_opposite_edge, true, Rule::CornerTouch, &premises);
+            self.set(&v_opposite_edge, true, Rule::CornerTouch, &premises);
         }
     }
 
@@ -637,6 +618,7 @@ impl Solver {
                 }
             }
         }
+        let premises = [Premise::Cell(*cell)];
         if closed_corners.len() >= 3 {
             // Then all the edges of the cell must be on or all the edges of the cell must be off.
             // First check that all the edges in the cell are unknown, because if at least one is
@@ -644,11 +626,11 @@ impl Solver {
             let edges = self.edges_from_cell(cell).clone();
             for e in edges {
                 if e.is_on {
-                    for x in edges { self.set(&x, true); }
+                    for x in edges { self.set(&x, true, Rule::ClosedCorners, &premises); }
                     return;
                 }
                 if e.is_off {
-                    for x in edges { self.set(&x, false); }
+                    for x in edges { self.set(&x, false, Rule::ClosedCorners, &premises); }
                     return;
                 }
             }
@@ -656,7 +638,7 @@ impl Solver {
             // progress, then that means all these edges must be off otherwise we would have a loop
             // that won't contain all the egdes.
             if self.paths.num_paths() > 0 || !self.can_be_single_cell {
-                for x in edges { self.set(&x, false); }
+                for x in edges { self.set(&x, false, Rule::ClosedCorners, &premises); }
             }
         } else if closed_corners.len() == 2 {
             // If the cell has 2 consecutive closed corners, then 3 of the edges form a path. All
@@ -682,8 +664,8 @@ impl Solver {
                 // Turn the edges on and off to highlight where the contradiction is.
                 let edges = self.edges_from_cell(cell).clone();
                 for x in edges {
-                    self.set(&x, false);
-                    self.set(&x, true);
+                    self.set(&x, false, Rule::ClosedCorners, &premises);
+                    self.set(&x, true, Rule::ClosedCorners, &premises);
                 }
             } else if hint == 3 {
                 // Then we should have already turned the relevant edges on since we only need
@@ -722,12 +704,12 @@ impl Solver {
         //    ┄ ─
         //  0 · ·┆
         //    ┄ ┄
-        let corners: Vec<Coordinate> = self.recently_affected_corners.iter().cloned().collect();
+        let corners: Vec<Coordinate> = self.recently_affected_corners.iter().map(|i| self.node(i)).collect();
         for corner in corners {
             if self.status != Status::InProgress {
                 return;
             }
-            self.recently_affected_corners.remove(&corner);
+            self.recently_affected_corners.remove(self.node_id(&corner));
 
             let is_on = |e: Option<Edge>| match e { Some(e) => e.is_on, None => false };
             let is_off = |e: Option<Edge>| match e { Some(e) => e.is_off, None => true };
@@ -784,44 +766,18 @@ impl Solver {
     }
 
     fn satisfies_contraints(&self) -> bool {
-        let n = self.puzzle.size;
-        // Check if there is a single loop.
-        if !self.paths.has_loop() || self.paths.num_paths() != 1 {
-            return false;
-        }
         // Check if each edge is either on or off (if neither, then assume off).
-        for i in 0..n {
-            for j in 0..(n+1) {
-                if self.h_edges[j][i].is_on && self.h_edges[j][i].is_off { return false; }
-                if self.v_edges[i][j].is_on && self.v_edges[i][j].is_off { return false; }
-            }
-        }
-        // Check if each hint is satisfied.
-        for i in 0..n {
-            for j in 0..n {
-                match self.puzzle.grid[i][j] {
-                    None => {
-                        // Do nothing
-                    },
-                    Some(x) => {
-                        let edges = self.edges_from_cell(&Coordinate(i, j));
-                        let mut count = 0;
-                        for e in edges {
-                            if e.is_on { count += 1; }
-                        }
-                        if count != x {
-                            return false;
-                        }
-                    }
-                }
-            }
+        let mut both = self.on.clone();
+        both.intersect_with(&self.off);
+        if !both.is_empty() {
+            return false;
         }
-        // If all three checks passed, then the puzzle is solved!
-        return true;
+        // Check if the edges that are on form a single loop that satisfies each hint.
+        return verify(&self.puzzle, &self.edges_on()).is_ok();
     }
 
     fn check_if_connected(&mut self) {
-        if !self.all_in_same_connect_component(self.paths.get_endpoints()) {
+        if !self.all_in_same_connect_component(&self.paths.get_endpoints()) {
             self.status = Status::Unsolvable;
         }
     }
@@ -832,7 +788,6 @@ impl Solver {
         if self.paths.num_paths() == 0 {
             self.initial_solve();
             // println!("After initial solve:\n{}", self.to_string());
-            // println!("{}\n", self.inside_tracker.to_string());
         }
         self.change_flag = true;
         while self.change_flag && self.status == Status::InProgress {
@@ -840,38 +795,28 @@ impl Solver {
             self.reset_corner_data();
             self.apply_local_single_loop_contraints();
             // println!("After single loop arguments:\n{}\n", self.to_string());
-            // println!("{}\n", self.inside_tracker.to_string());
             self.apply_cell_constraints();
             // println!("After cell arguments:\n{}\n", self.to_string());
-            // println!("{}\n", self.inside_tracker.to_string());
             self.apply_corner_arguments();
             // println!("After corner arguments:\n{}\n", self.to_string());
-            // println!("{}\n", self.inside_tracker.to_string());
             self.apply_node_constraints();
             // println!("After node arguments:\n{}\n", self.to_string());
-            // println!("{}\n", self.inside_tracker.to_string());
             self.outer_inner_border_argument();
             // println!("After border arguments:\n{}\n", self.to_string());
-            // println!("{}\n", self.inside_tracker.to_string());
             self.check_if_connected();
             if self.status == Status::InProgress && !self.change_flag {
                 self.inside_tracker.apply_insides_must_be_connected_arguments();
-                let inferences = self.inside_tracker.get_inferences();
-                for e in inferences {
-                    self.set(&e, e.is_on);
-                }
+                self.apply_inside_inferences(Rule::InsidesConnected);
                 // println!("After inside must be connected arguments:\n{}\n", self.to_string());
-                // println!("{}\n", self.inside_tracker.to_string());
             }
             if self.status == Status::InProgress && !self.change_flag {
                 // Check the corners again just to be sure.
                 // TODO: Figure out why this is needed sometimes.
                 for endpoint in self.paths.get_endpoints() {
-                    self.recently_affected_corners.insert(endpoint);
+                    self.recently_affected_corners.insert(self.node_id(&endpoint));
                 }
                 self.apply_corner_arguments();
                 // println!("After corner arguments (again):\n{}\n", self.to_string());
-                // println!("{}\n", self.inside_tracker.to_string());
             }
             if self.status == Status::InProgress && self.paths.has_loop() {
                 // If a loop has been made, then the puzzle is over.
@@ -883,11 +828,22 @@ impl Solver {
             }
         }
         // If all the edges are off, then it's impossible to solve
-        if self.num_off == 2 * self.puzzle.size * (self.puzzle.size + 1) {
+        if self.num_off == self.num_edges() {
             self.status = Status::Unsolvable;
         }
     }
 
+    // Tells the other copies searching in parallel about the solutions found.
+    fn record_solutions(&self, solutions: &[Solver]) {
+        if let Some(search) = &self.search {
+            for s in solutions.iter().filter(|s| s.status == Status::UniqueSolution) {
+                let mut s = s.clone();
+                s.search = None;
+                search.record(s);
+            }
+        }
+    }
+
     pub fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Solver> {
         self.depth_needed = depth;
         let start = Instant::now();
@@ -900,6 +856,7 @@ impl Solver {
         } else if self.status == Status::UniqueSolution {
             solutions.push(self.clone());
         }
+        self.record_solutions(&solutions);
 
         let duration = start.elapsed();
         let indent = " ".repeat(8 * depth as usize);
@@ -909,9 +866,48 @@ impl Solver {
             println!("{}Status: {:?}", indent, self.status);
             println!("{}Depth: {:?}", indent, depth);
             println!("{}Solutions #: {}", indent, solutions.len());
-            // println!("{}\n", self.inside_tracker.to_string());
         }
 
         return solutions;
     }
 }
+
+impl PuzzleSolver for Solver {
+    type Puzzle = Puzzle;
+
+    fn new(p: Puzzle) -> Solver {
+        return Solver::new(p);
+    }
+
+    fn non_recursive_solve(&mut self) {
+        Solver::non_recursive_solve(self);
+    }
+
+    fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Solver> {
+        return Solver::full_solve(self, depth, should_log);
+    }
+
+    fn search_in_parallel(&mut self, parallel_depth: u8) {
+        self.search = Some(Search::new(parallel_depth));
+    }
+
+    fn status(&self) -> Status {
+        return self.status;
+    }
+
+    fn depth_needed(&self) -> u8 {
+        return self.depth_needed;
+    }
+
+    fn grade(&self) -> Grade {
+        return grader::grade(self.trace.iter().map(|s| s.rule));
+    }
+
+    fn solution(&self) -> Option<Solution> {
+        return Solver::solution(self);
+    }
+
+    fn to_string(&self) -> String {
+        return Solver::to_string(self);
+    }
+}
@@ -5,8 +5,9 @@ use super::coordinate::Coordinate;
 use super::direction::HDirection;
 use super::direction::VDirection;
 use super::edge::Edge;
-
-use std::collections::HashSet;
+use super::trace::Premise;
+use super::trace::Rule;
+use crate::bitset::BitSet;
 
 // Every node in the puzzle will end up with degree 0 or 2.
 // Every node in the puzzle is the corner of some cells.
@@ -31,24 +32,19 @@ pub enum EntryStatus {
     NotEntryForSure,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct NodeDirectionTriplet {
-    node: Coordinate,
-    hd: HDirection,
-    vd: VDirection,
-}
-
+// The corners looked at since the last reset, indexed by 4 * node_id plus the direction of the
+// cell from the node.
 #[derive(Clone, Debug)]
 pub struct CornerSolverData {
-    pub entries_looked_at: HashSet<NodeDirectionTriplet>,
-    pub removals_looked_at: HashSet<NodeDirectionTriplet>,
+    pub entries_looked_at: BitSet,
+    pub removals_looked_at: BitSet,
 }
 
 impl CornerSolverData {
-    pub fn new() -> CornerSolverData {
+    pub fn new(num_nodes: usize) -> CornerSolverData {
         return CornerSolverData{
-            entries_looked_at: HashSet::new(),
-            removals_looked_at: HashSet::new(),
+            entries_looked_at: BitSet::new(4 * num_nodes),
+            removals_looked_at: BitSet::new(4 * num_nodes),
         }
     }
     fn reset(&mut self) {
@@ -62,6 +58,12 @@ impl Solver {
         self.corner_solver_data.reset();
     }
 
+    fn corner_id(&self, node: &Coordinate, hd: &HDirection, vd: &VDirection) -> usize {
+        let h = match hd { HDirection::RIGHT => 0, HDirection::LEFT => 1 };
+        let v = match vd { VDirection::UP => 0, VDirection::DOWN => 1 };
+        return 4 * self.node_id(node) + 2 * h + v;
+    }
+
     fn get_entry_status(&mut self, cell: &Coordinate, hd: &HDirection, vd: &VDirection) -> EntryStatus {
         let n = self.node_from_cell(cell, hd, vd);
         let h_edge = self.edge_from_cell(cell, &vd.to_direction());
@@ -88,17 +90,14 @@ impl Solver {
         let h_edge = self.edge_from_node(&node, &hd.to_direction());
         let v_edge = self.edge_from_node(&node, &vd.to_direction());
         if h_edge.is_none() {
-            if v_edge.is_some() { self.set(&v_edge.unwrap(), true); }
+            if v_edge.is_some() { self.set(&v_edge.unwrap(), true, Rule::CornerEntry, &[Premise::Node(*node)]); }
         } else if v_edge.is_none() {
-            if h_edge.is_some() { self.set(&h_edge.unwrap(), true); }
+            if h_edge.is_some() { self.set(&h_edge.unwrap(), true, Rule::CornerEntry, &[Premise::Node(*node)]); }
         } else {
-            let triple = NodeDirectionTriplet {
-                node: node.clone(), hd: hd.clone(), vd: vd.clone(),
-            };
-            if self.corner_solver_data.entries_looked_at.contains(&triple) {
+            let corner = self.corner_id(node, hd, vd);
+            if !self.corner_solver_data.entries_looked_at.insert(corner) {
                 return;
             } else {
-                self.corner_solver_data.entries_looked_at.insert(triple);
                 let c = self.cell_from_node(&node, &hd, &vd).unwrap();
                 self.apply_entry_node_inference(&c, &hd.opposite(), &vd.opposite());
             }
@@ -109,17 +108,14 @@ impl Solver {
         let h_edge = self.edge_from_node(&node, &hd.to_direction());
         let v_edge = self.edge_from_node(&node, &vd.to_direction());
         if h_edge.is_none() {
-            if v_edge.is_some() { self.set(&v_edge.unwrap(), false); }
+            if v_edge.is_some() { self.set(&v_edge.unwrap(), false, Rule::CornerNoEntry, &[Premise::Node(*node)]); }
         } else if v_edge.is_none() {
-            if h_edge.is_some() { self.set(&h_edge.unwrap(), false); }
+            if h_edge.is_some() { self.set(&h_edge.unwrap(), false, Rule::CornerNoEntry, &[Premise::Node(*node)]); }
         } else {
-            let triple = NodeDirectionTriplet {
-                node: node.clone(), hd: hd.clone(), vd: vd.clone(),
-            };
-            if self.corner_solver_data.removals_looked_at.contains(&triple) {
+            let corner = self.corner_id(node, hd, vd);
+            if !self.corner_solver_data.removals_looked_at.insert(corner) {
                 return;
             } else {
-                self.corner_solver_data.removals_looked_at.insert(triple);
                 let c = self.cell_from_node(&node, &hd, &vd).unwrap();
                 self.apply_non_entry_node_inference(&c, &hd.opposite(), &vd.opposite());
             }
@@ -132,30 +128,31 @@ impl Solver {
         let v_edge = self.edge_from_cell(cell, &hd.to_direction());
         let other_h_edge = self.edge_from_cell(cell, &vd.opposite().to_direction());
         let other_v_edge = self.edge_from_cell(cell, &hd.opposite().to_direction());
+        let premises = [Premise::Cell(*cell), Premise::Node(self.node_from_cell(cell, hd, vd))];
 
         // If corner is an entry corner for the cell, then h_edge and v_edge don't match.
-        if h_edge.is_on { self.set(&v_edge, false); }
-        if h_edge.is_off { self.set(&v_edge, true); }
-        if v_edge.is_on { self.set(&h_edge, false); }
-        if v_edge.is_off { self.set(&h_edge, true); }
+        if h_edge.is_on { self.set(&v_edge, false, Rule::CornerEntry, &premises); }
+        if h_edge.is_off { self.set(&v_edge, true, Rule::CornerEntry, &premises); }
+        if v_edge.is_on { self.set(&h_edge, false, Rule::CornerEntry, &premises); }
+        if v_edge.is_off { self.set(&h_edge, true, Rule::CornerEntry, &premises); }
 
         // If the cell has a hint, we might be able to make more inferences.
         let hint = self.puzzle.grid[cell.0][cell.1];
         match hint {
             Some(0) | Some(4) => { self.status = Status::Unsolvable; return; },
             Some(1) => {
-                self.set(&other_v_edge, false);
-                self.set(&other_h_edge, false);
+                self.set(&other_v_edge, false, Rule::CornerEntry, &premises);
+                self.set(&other_h_edge, false, Rule::CornerEntry, &premises);
             },
             Some(3) => {
-                self.set(&other_v_edge, true);
-                self.set(&other_h_edge, true);
+                self.set(&other_v_edge, true, Rule::CornerEntry, &premises);
+                self.set(&other_h_edge, true, Rule::CornerEntry, &premises);
             },
             Some(2) => {
-                if other_h_edge.is_on { self.set(&other_v_edge, false); }
-                if other_h_edge.is_off { self.set(&other_v_edge, true); }
-                if other_v_edge.is_on { self.set(&other_h_edge, false); }
-                if other_v_edge.is_off { self.set(&other_h_edge, true); }
+                if other_h_edge.is_on { self.set(&other_v_edge, false, Rule::CornerEntry, &premises); }
+                if other_h_edge.is_off { self.set(&other_v_edge, true, Rule::CornerEntry, &premises); }
+                if other_v_edge.is_on { self.set(&other_h_edge, false, Rule::CornerEntry, &premises); }
+                if other_v_edge.is_off { self.set(&other_h_edge, true, Rule::CornerEntry, &premises); }
 
                 self.enter_node(&opp_corner, &hd.opposite(), &vd.opposite());
             }
@@ -188,31 +185,32 @@ impl Solver {
         let v_edge = self.edge_from_cell(cell, &hd.to_direction());
         let other_h_edge = self.edge_from_cell(cell, &vd.opposite().to_direction());
         let other_v_edge = self.edge_from_cell(cell, &hd.opposite().to_direction());
+        let premises = [Premise::Cell(*cell), Premise::Node(self.node_from_cell(cell, hd, vd))];
 
         // If corner is NOT an entry corner for the cell, then h_edge and v_edge match.
-        if h_edge.is_on { self.set(&v_edge, true); }
-        if h_edge.is_off { self.set(&v_edge, false); }
-        if v_edge.is_on { self.set(&h_edge, true); }
-        if v_edge.is_off { self.set(&h_edge, false); }
+        if h_edge.is_on { self.set(&v_edge, true, Rule::CornerNoEntry, &premises); }
+        if h_edge.is_off { self.set(&v_edge, false, Rule::CornerNoEntry, &premises); }
+        if v_edge.is_on { self.set(&h_edge, true, Rule::CornerNoEntry, &premises); }
+        if v_edge.is_off { self.set(&h_edge, false, Rule::CornerNoEntry, &premises); }
 
         // If the cell has a hint, we might be able to make more inferences.
         let hint = self.puzzle.grid[cell.0][cell.1];
         match hint {
             Some(1) => {
-                self.set(&v_edge, false);
-                self.set(&h_edge, false);
+                self.set(&v_edge, false, Rule::CornerNoEntry, &premises);
+                self.set(&h_edge, false, Rule::CornerNoEntry, &premises);
                 self.enter_node(&opp_corner, &hd.opposite(), &vd.opposite());
             },
             Some(3) => {
-                self.set(&v_edge, true);
-                self.set(&h_edge, true);
+                self.set(&v_edge, true, Rule::CornerNoEntry, &premises);
+                self.set(&h_edge, true, Rule::CornerNoEntry, &premises);
                 self.enter_node(&opp_corner, &hd.opposite(), &vd.opposite());
             },
             Some(2) => {
-                if other_h_edge.is_on { self.set(&other_v_edge, true); }
-                if other_h_edge.is_off { self.set(&other_v_edge, false); }
-                if other_v_edge.is_on { self.set(&other_h_edge, true); }
-                if other_v_edge.is_off { self.set(&other_h_edge, false); }
+                if other_h_edge.is_on { self.set(&other_v_edge, true, Rule::CornerNoEntry, &premises); }
+                if other_h_edge.is_off { self.set(&other_v_edge, false, Rule::CornerNoEntry, &premises); }
+                if other_v_edge.is_on { self.set(&other_h_edge, true, Rule::CornerNoEntry, &premises); }
+                if other_v_edge.is_off { self.set(&other_h_edge, false, Rule::CornerNoEntry, &premises); }
 
                 // TODO: This argument could be generelized more. Basicly, if we know that we have
                 // a no entry at a corner, then we know that we have at most one edge one for the
@@ -222,8 +220,9 @@ impl Solver {
                     Some(neighbour) => {
                         let hint = self.puzzle.grid[neighbour.0][neighbour.1];
                         if hint.is_some() && hint.unwrap() == 3 {
-                            self.set(&self.edge_from_cell(&neighbour, &hd.opposite().to_direction()), true);
-                            self.set(&self.edge_from_cell(&neighbour, &vd.to_direction()), true);
+                            let premises = [premises[0], premises[1], Premise::Cell(neighbour)];
+                            self.set(&self.edge_from_cell(&neighbour, &hd.opposite().to_direction()), true, Rule::CornerNoEntry, &premises);
+                            self.set(&self.edge_from_cell(&neighbour, &vd.to_direction()), true, Rule::CornerNoEntry, &premises);
                         }
                     },
                     None => {},
@@ -234,8 +233,9 @@ impl Solver {
                     Some(neighbour) => {
                         let hint = self.puzzle.grid[neighbour.0][neighbour.1];
                         if hint.is_some() && hint.unwrap() == 3 {
-                            self.set(&self.edge_from_cell(&neighbour, &vd.opposite().to_direction()), true);
-                            self.set(&self.edge_from_cell(&neighbour, &hd.to_direction()), true);
+                            let premises = [premises[0], premises[1], Premise::Cell(neighbour)];
+                            self.set(&self.edge_from_cell(&neighbour, &vd.opposite().to_direction()), true, Rule::CornerNoEntry, &premises);
+                            self.set(&self.edge_from_cell(&neighbour, &hd.to_direction()), true, Rule::CornerNoEntry, &premises);
                         }
                     },
                     None => {},
@@ -263,10 +263,10 @@ impl Solver {
             }
             if potential_exits.len() <= 1 {
                 // If there is just one potential exit, then all the edges must be off.
-                self.set(&h_edge, false);
-                self.set(&v_edge, false);
-                self.set(&other_h_edge, false);
-                self.set(&other_v_edge, false);
+                self.set(&h_edge, false, Rule::CornerNoEntry, &premises);
+                self.set(&v_edge, false, Rule::CornerNoEntry, &premises);
+                self.set(&other_h_edge, false, Rule::CornerNoEntry, &premises);
+                self.set(&other_v_edge, false, Rule::CornerNoEntry, &premises);
             }
 
             if potential_exits.len() == 2 {
@@ -1,22 +1,34 @@
 use super::Solver;
 use super::Status;
+use super::trace::Premise;
+use super::trace::Rule;
+use super::edge::Edge;
+use crate::solver;
 
 // Solver methods based on making a guess and seeing if we end up with a contradiction.
 impl Solver {
     pub fn depth_solve(& mut self, depth: u8, should_log: bool) -> Vec<Solver> {
-        let n = self.puzzle.size;
-
         while !self.remaining_edges.is_empty() {
+            if let Some(search) = &self.search {
+                if search.is_cancelled() {
+                    // Another copy found a second solution.
+                    self.status = Status::MultipleSolutions;
+                    return search.found();
+                }
+            }
+
             let mut solutions: Vec<Solver> = Vec::new();
             // Prioritize edges next to hints as they tend to lead to more inferences.
-            let e = if self.remaining_edges_next_to_hints.is_empty() {
-                self.remaining_edges.iter().next().unwrap().clone()
-            } else {
-                self.remaining_edges_next_to_hints.iter().next().unwrap().clone()
+            let e = match self.remaining_edges_next_to_hints.iter().next() {
+                Some(id) => self.edge(id),
+                None => self.edge(self.remaining_edges.iter().next().unwrap()),
             };
+            if self.search.as_ref().is_some_and(|s| s.should_fork(depth)) {
+                return self.depth_solve_in_parallel(&e, depth, should_log);
+            }
             // Check if setting the edge off leads to a contradiction
             let mut copy = self.clone();
-            copy.set(&e, false);
+            copy.set(&e, false, Rule::Guess, &[Premise::Edge(e)]);
             let mut copy_solutions = copy.full_solve(depth + 1, should_log);
             solutions.append(&mut copy_solutions);
             if copy.depth_needed > self.depth_needed {
@@ -24,7 +36,7 @@ impl Solver {
             }
             // If setting the edge off leads to a contradiction, then the edge must be on.
             if copy.status == Status::Unsolvable {
-                self.set(&e, true);
+                self.set(&e, true, Rule::TrialAndError, &[Premise::Edge(e)]);
                 // Now that we have new information, let's apply our non-recursive methods as well.
                 self.non_recursive_solve();
                 // If we end up with an unsovlable or uniquely solvable puzzle, then we are done.
@@ -44,7 +56,7 @@ impl Solver {
             // If setting the edge off leads to a single solution, check what happens when we set
             // the edge on.
             copy = self.clone();
-            copy.set(&e, true);
+            copy.set(&e, true, Rule::Guess, &[Premise::Edge(e)]);
             copy_solutions = copy.full_solve(depth + 1, should_log);
             solutions.append(&mut copy_solutions);
             if copy.depth_needed > self.depth_needed {
@@ -71,4 +83,40 @@ impl Solver {
             return Vec::new();
         }
     }
+
+    // Explores the edge being off and on at the same time, each on its own thread.
+    fn depth_solve_in_parallel(&mut self, e: &Edge, depth: u8, should_log: bool) -> Vec<Solver> {
+        let mut off = self.clone();
+        off.set(e, false, Rule::Guess, &[Premise::Edge(*e)]);
+        let mut on = self.clone();
+        on.set(e, true, Rule::Guess, &[Premise::Edge(*e)]);
+
+        let mut branches = [off, on];
+        let mut results = solver::solve_branches(&mut branches, depth + 1, should_log);
+        let needed = solver::depth_after_guess(branches[0].depth_needed, results[0].len(), branches[1].depth_needed);
+        if needed > self.depth_needed {
+            self.depth_needed = needed;
+        }
+        if results[0].is_empty() {
+            // depth_solve would have set the edge on by trial and error instead of guessing it, so
+            // the solutions found with it on log it the same way.
+            let start = self.trace.len();
+            for s in results[1].iter_mut() {
+                s.trace[start].rule = Rule::TrialAndError;
+            }
+        }
+
+        let search = self.search.as_ref().unwrap();
+        if search.is_cancelled() {
+            self.status = Status::MultipleSolutions;
+            return search.found();
+        }
+        let solutions = results.concat();
+        self.status = match solutions.len() {
+            0 => Status::Unsolvable,
+            1 => Status::UniqueSolution,
+            _ => Status::MultipleSolutions,
+        };
+        return solutions;
+    }
 }
@@ -1,5 +1,7 @@
 use super::Solver;
 use super::Status;
+use super::trace::Premise;
+use super::trace::Rule;
 
 use super::coordinate::Coordinate;
 use super::direction::Direction;
@@ -11,14 +13,14 @@ impl Solver {
     fn hint_analysis(&mut self) {
         let mut has_4 = false;
         let mut has_2_or_3 = false;
-        for i in 0..self.puzzle.size {
-            for j in 0..self.puzzle.size {
+        for i in 0..self.puzzle.height {
+            for j in 0..self.puzzle.width {
                 match self.puzzle.grid[i][j] {
                     Some(0) => {
                         // If a cell has the 0 hint, then all the edges must be off.
                         let c = Coordinate(i, j);
                         for d in Direction::iter() {
-                            self.set(&self.edge_from_cell(&c, &d), false);
+                            self.set(&self.edge_from_cell(&c, d), false, Rule::ZeroHint, &[Premise::Cell(c)]);
                         }
                     },
                     Some(2) => {
@@ -39,7 +41,7 @@ impl Solver {
                         has_4 = true;
                         let c = Coordinate(i, j);
                         for d in Direction::iter() {
-                            self.set(&self.edge_from_cell(&c, &d), true);
+                            self.set(&self.edge_from_cell(&c, d), true, Rule::FourHint, &[Premise::Cell(c)]);
                         }
                     },
                     // If the cell has the 1 hint or no hints, no inferences can be made.
@@ -54,9 +56,9 @@ impl Solver {
 
     fn corner_hint_analysis(&mut self) {
         for hd in [HDirection::LEFT, HDirection::RIGHT] {
-            let col = if HDirection::LEFT.eq(&hd) {0} else {self.puzzle.size};
+            let col = if HDirection::LEFT.eq(&hd) {0} else {self.puzzle.width};
             for vd in [VDirection::UP, VDirection::DOWN] {
-                let row = if VDirection::UP.eq(&vd) {0} else {self.puzzle.size};
+                let row = if VDirection::UP.eq(&vd) {0} else {self.puzzle.height};
                 self.remove_entry_at_node(&Coordinate(row, col), &hd.opposite(), &vd.opposite());
             }
         }
@@ -79,8 +81,8 @@ impl Solver {
         // or at least one other hint that is a non-adjacent 1.
         let mut number_of_3s = 0;
         let mut has_2 = false;
-        for i in 0..self.puzzle.size {
-            for j in 0..self.puzzle.size {
+        for i in 0..self.puzzle.height {
+            for j in 0..self.puzzle.width {
                 match self.puzzle.grid[i][j] {
                     // TODO, detect non adjacent 1s,
                     Some(2) => { has_2 = true; },
@@ -90,33 +92,35 @@ impl Solver {
             }
         }
         let could_be_loop = number_of_3s <= 2 && !has_2;
-        for i in 0..self.puzzle.size {
-            for j in 0..self.puzzle.size {
+        for i in 0..self.puzzle.height {
+            for j in 0..self.puzzle.width {
                 if self.is_3(i, j) {
                     if self.is_3(i + 1, j) {
-                        self.set(&self.h_edges[i][j].clone(), true);
+                        let premises = [Premise::Cell(Coordinate(i, j)), Premise::Cell(Coordinate(i + 1, j))];
+                        self.set(&self.h_edge(i, j), true, Rule::AdjacentThrees, &premises);
                         if !could_be_loop {
-                            self.set(&self.h_edges[i + 1][j].clone(), true);
+                            self.set(&self.h_edge(i + 1, j), true, Rule::AdjacentThrees, &premises);
                         }
-                        self.set(&self.h_edges[i + 2][j].clone(), true);
+                        self.set(&self.h_edge(i + 2, j), true, Rule::AdjacentThrees, &premises);
                         if j > 0 {
-                            self.set(&self.h_edges[i + 1][j - 1].clone(), false);
+                            self.set(&self.h_edge(i + 1, j - 1), false, Rule::AdjacentThrees, &premises);
                         }
-                        if j < self.puzzle.size - 1 {
-                            self.set(&self.h_edges[i + 1][j + 1].clone(), false);
+                        if j < self.puzzle.width - 1 {
+                            self.set(&self.h_edge(i + 1, j + 1), false, Rule::AdjacentThrees, &premises);
                         }
                     }
                     if self.is_3(i, j + 1) {
-                        self.set(&self.v_edges[i][j].clone(), true);
+                        let premises = [Premise::Cell(Coordinate(i, j)), Premise::Cell(Coordinate(i, j + 1))];
+                        self.set(&self.v_edge(i, j), true, Rule::AdjacentThrees, &premises);
                         if !could_be_loop {
-                            self.set(&self.v_edges[i][j + 1].clone(), true);
+                            self.set(&self.v_edge(i, j + 1), true, Rule::AdjacentThrees, &premises);
                         }
-                        self.set(&self.v_edges[i][j + 2].clone(), true);
+                        self.set(&self.v_edge(i, j + 2), true, Rule::AdjacentThrees, &premises);
                         if i > 0 {
-                            self.set(&self.v_edges[i - 1][j + 1].clone(), false);
+                            self.set(&self.v_edge(i - 1, j + 1), false, Rule::AdjacentThrees, &premises);
                         }
-                        if i < self.puzzle.size - 1 {
-                            self.set(&self.v_edges[i + 1][j + 1].clone(), false);
+                        if i < self.puzzle.height - 1 {
+                            self.set(&self.v_edge(i + 1, j + 1), false, Rule::AdjacentThrees, &premises);
                         }
                     }
                 }
@@ -127,8 +131,8 @@ impl Solver {
     fn handle_diagonal_3(&mut self) {
         // If a 3 is diagonal to another 3 (with however many 2s in between), then their edges in
         // the their opposite corners are on.
-        for i in 0..self.puzzle.size {
-            for j in 0..self.puzzle.size {
+        for i in 0..self.puzzle.height {
+            for j in 0..self.puzzle.width {
                 if self.is_3(i, j) {
                     // Look at bottom right diagonal
                     let mut next = 1;
@@ -136,10 +140,11 @@ impl Solver {
                         next = next + 1;
                     }
                     if self.is_3(i + next, j + next) {
-                        self.set(&self.v_edges[i][j].clone(), true);
-                        self.set(&self.h_edges[i][j].clone(), true);
-                        self.set(&self.v_edges[i + next][j + next + 1].clone(), true);
-                        self.set(&self.h_edges[i + next + 1][j + next].clone(), true);
+                        let premises: Vec<Premise> = (0..=next).map(|k| Premise::Cell(Coordinate(i + k, j + k))).collect();
+                        self.set(&self.v_edge(i, j), true, Rule::DiagonalThrees, &premises);
+                        self.set(&self.h_edge(i, j), true, Rule::DiagonalThrees, &premises);
+                        self.set(&self.v_edge(i + next, j + next + 1), true, Rule::DiagonalThrees, &premises);
+                        self.set(&self.h_edge(i + next + 1, j + next), true, Rule::DiagonalThrees, &premises);
                     }
                     // Look at top right diagonal
                     next = 1;
@@ -147,10 +152,11 @@ impl Solver {
                         next = next + 1;
                     }
                     if j >= next && self.is_3(i + next, j - next) {
-                        self.set(&self.v_edges[i][j + 1].clone(), true);
-                        self.set(&self.h_edges[i][j].clone(), true);
-                        self.set(&self.v_edges[i + next][j - next].clone(), true);
-                        self.set(&self.h_edges[i + next + 1][j - next].clone(), true);
+                        let premises: Vec<Premise> = (0..=next).map(|k| Premise::Cell(Coordinate(i + k, j - k))).collect();
+                        self.set(&self.v_edge(i, j + 1), true, Rule::DiagonalThrees, &premises);
+                        self.set(&self.h_edge(i, j), true, Rule::DiagonalThrees, &premises);
+                        self.set(&self.v_edge(i + next, j - next), true, Rule::DiagonalThrees, &premises);
+                        self.set(&self.h_edge(i + next + 1, j - next), true, Rule::DiagonalThrees, &premises);
                     }
                 }
             }
@@ -159,26 +165,36 @@ impl Solver {
 
     // TODO: Figure out how to generalize this argument
     pub fn handle_3_next_to_1_on_edge(&mut self) {
-        let n = self.puzzle.size;
-        for i in 1..(self.puzzle.size - 1) {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
+        for i in 1..(h - 1) {
             if self.is_3(i, 0) {
                 if self.is_1(i - 1, 0) || self.is_1(i + 1, 0) {
-                    self.set(&self.v_edges[i][0].clone(), true);
+                    let one = if self.is_1(i - 1, 0) { i - 1 } else { i + 1 };
+                    let premises = [Premise::Cell(Coordinate(i, 0)), Premise::Cell(Coordinate(one, 0))];
+                    self.set(&self.v_edge(i, 0), true, Rule::ThreeNextToOneOnBorder, &premises);
                 }
             }
-            if self.is_3(i, n - 1) {
-                if self.is_1(i - 1, n - 1) || self.is_1(i + 1, n - 1) {
-                    self.set(&self.v_edges[i][n].clone(), true);
+            if self.is_3(i, w - 1) {
+                if self.is_1(i - 1, w - 1) || self.is_1(i + 1, w - 1) {
+                    let one = if self.is_1(i - 1, w - 1) { i - 1 } else { i + 1 };
+                    let premises = [Premise::Cell(Coordinate(i, w - 1)), Premise::Cell(Coordinate(one, w - 1))];
+                    self.set(&self.v_edge(i, w), true, Rule::ThreeNextToOneOnBorder, &premises);
                 }
             }
-            if self.is_3(0, i) {
-                if self.is_1(0, i - 1) || self.is_1(0, i + 1) {
-                    self.set(&self.h_edges[0][i].clone(), true);
+        }
+        for j in 1..(w - 1) {
+            if self.is_3(0, j) {
+                if self.is_1(0, j - 1) || self.is_1(0, j + 1) {
+                    let one = if self.is_1(0, j - 1) { j - 1 } else { j + 1 };
+                    let premises = [Premise::Cell(Coordinate(0, j)), Premise::Cell(Coordinate(0, one))];
+                    self.set(&self.h_edge(0, j), true, Rule::ThreeNextToOneOnBorder, &premises);
                 }
             }
-            if self.is_3(n - 1, i) {
-                if self.is_1(n - 1, i - 1) || self.is_1(n - 1, i + 1) {
-                    self.set(&self.h_edges[n][i].clone(), true);
+            if self.is_3(h - 1, j) {
+                if self.is_1(h - 1, j - 1) || self.is_1(h - 1, j + 1) {
+                    let one = if self.is_1(h - 1, j - 1) { j - 1 } else { j + 1 };
+                    let premises = [Premise::Cell(Coordinate(h - 1, j)), Premise::Cell(Coordinate(h - 1, one))];
+                    self.set(&self.h_edge(h, j), true, Rule::ThreeNextToOneOnBorder, &premises);
                 }
             }
         }
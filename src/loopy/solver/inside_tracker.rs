@@ -2,98 +2,63 @@ use super::coordinate::Coordinate;
 use std::collections::HashMap;
 use super::edge::Edge;
 use super::edge::EdgeType;
-
-use std::collections::HashSet;
-use std::collections::VecDeque;
-
-
-#[derive(Clone, Debug)]
-pub struct InsideInfo {
-    c: Coordinate,
-    is_inside: bool,
-    is_outside: bool,
-    matching_cells: HashSet<Coordinate>,
-    opposite_cells: HashSet<Coordinate>,
-}
+use crate::bitset::BitSet;
 
 
+// Cells are indexed by row * width + col.
 #[derive(Clone, Debug)]
 pub struct InsideTracker {
-    size: usize,
-    data: Vec<Vec<InsideInfo>>,
-    inside_cells: HashSet<Coordinate>,
-    outside_cells: HashSet<Coordinate>,
+    width: usize,
+    height: usize,
+    // The cells known to be on the same side of the loop as each cell, and on the other side.
+    matching_cells: Vec<BitSet>,
+    opposite_cells: Vec<BitSet>,
+    inside_cells: BitSet,
+    outside_cells: BitSet,
     newly_set_cells: Vec<Coordinate>,
     pub found_contradiction: bool,
 }
 
 impl InsideTracker {
-    pub fn new(size:usize) -> InsideTracker {
-        let mut data: Vec<Vec<InsideInfo>> = Vec::new();
-        for i in 0..size {
-            let mut row = Vec::new();
-            for j in 0..size {
-                let mut matching_cells = HashSet::new();
-                matching_cells.insert(Coordinate(i,j));
-                row.push(InsideInfo {
-                    c: Coordinate(i, j),
-                    is_inside: false,
-                    is_outside: false,
-                    matching_cells,
-                    opposite_cells: HashSet::new(),
-                });
-            }
-            data.push(row);
+    pub fn new(width: usize, height: usize) -> InsideTracker {
+        let count = width * height;
+        let mut matching_cells = vec![BitSet::new(count); count];
+        for (i, m) in matching_cells.iter_mut().enumerate() {
+            m.insert(i);
         }
         return InsideTracker{
-            size,
-            data,
-            inside_cells: HashSet::new(),
-            outside_cells: HashSet::new(),
+            width,
+            height,
+            matching_cells,
+            opposite_cells: vec![BitSet::new(count); count],
+            inside_cells: BitSet::new(count),
+            outside_cells: BitSet::new(count),
             newly_set_cells: Vec::new(),
             found_contradiction: false};
     }
 
-    pub fn to_string(&self) -> String {
-        let n = self.size;
-        let mut rows: Vec<String> = Vec::new();
-
-        for i in 0..n {
-            let mut row = Vec::new();
-            for j in 0..n {
-                let mut symbol = "?";
-                if self.data[i][j].is_inside {
-                    symbol = "·";
-                }
-                if self.data[i][j].is_outside {
-                    symbol = " ";
-                }
-                if self.data[i][j].is_inside && self.data[i][j].is_outside{
-                    symbol = "!";
-                }
-                row.push(String::from(symbol));
-            }
-            rows.push(row.join(""));
-        }
+    fn id(&self, c: &Coordinate) -> usize {
+        return c.0 * self.width + c.1;
+    }
 
-        // Join and return rows.
-        return rows.join("\n");
+    fn cell(&self, id: usize) -> Coordinate {
+        return Coordinate(id / self.width, id % self.width);
     }
 
-    pub fn get_neighbours_to_check(&self) -> HashSet<Coordinate> {
-        let mut result: HashSet<Coordinate> = HashSet::new();
+    pub fn get_neighbours_to_check(&self) -> Vec<Coordinate> {
+        let mut result: Vec<Coordinate> = Vec::new();
         for c in self.newly_set_cells.clone() {
             if c.0 > 0 {
-                result.insert(Coordinate(c.0 - 1, c.1));
+                result.push(Coordinate(c.0 - 1, c.1));
             }
-            if c.0 < self.size - 1 {
-                result.insert(Coordinate(c.0 + 1, c.1));
+            if c.0 < self.height - 1 {
+                result.push(Coordinate(c.0 + 1, c.1));
             }
             if c.1 > 0 {
-                result.insert(Coordinate(c.0, c.1 - 1));
+                result.push(Coordinate(c.0, c.1 - 1));
             }
-            if c.1 < self.size - 1 {
-                result.insert(Coordinate(c.0, c.1 + 1));
+            if c.1 < self.width - 1 {
+                result.push(Coordinate(c.0, c.1 + 1));
             }
         }
         return result;
@@ -163,15 +128,15 @@ impl InsideTracker {
     }
 
     fn is_inside(&self, c: &Coordinate) -> bool {
-        return self.is_within_bounds(c) && self.data[c.0][c.1].is_inside;
+        return self.is_within_bounds(c) && self.inside_cells.contains(self.id(c));
     }
 
     fn is_outside(&self, c: &Coordinate) -> bool {
-        return !self.is_within_bounds(c) || self.data[c.0][c.1].is_outside;
+        return !self.is_within_bounds(c) || self.outside_cells.contains(self.id(c));
     }
 
     fn is_within_bounds(&self, c: &Coordinate) -> bool {
-        return c.0 < self.size && c.1 < self.size;
+        return c.0 < self.height && c.1 < self.width;
     }
 
     fn mark_cell(&mut self, c: &Coordinate, inside: bool) {
@@ -181,25 +146,24 @@ impl InsideTracker {
             }
             return;
         }
+        let id = self.id(c);
         let mut is_new_information = false;
-        if inside && !self.data[c.0][c.1].is_inside {
-            self.data[c.0][c.1].is_inside = true;
-            self.inside_cells.insert(c.clone());
+        if inside && !self.inside_cells.contains(id) {
+            self.inside_cells.insert(id);
             is_new_information = true;
-        } else if !inside && !self.data[c.0][c.1].is_outside {
-            self.data[c.0][c.1].is_outside = true;
-            self.outside_cells.insert(c.clone());
+        } else if !inside && !self.outside_cells.contains(id) {
+            self.outside_cells.insert(id);
             is_new_information = true;
         }
-        if self.data[c.0][c.1].is_inside && self.data[c.0][c.1].is_outside {
+        if self.inside_cells.contains(id) && self.outside_cells.contains(id) {
             self.found_contradiction = true;
         } else if is_new_information {
             self.newly_set_cells.push(c.clone());
-            for other in self.data[c.0][c.1].matching_cells.clone() {
-                self.make_cells_same(c, &other);
+            for other in self.matching_cells[id].clone().iter() {
+                self.make_cells_same(c, &self.cell(other));
             }
-            for other in self.data[c.0][c.1].opposite_cells.clone() {
-                self.make_cells_different(c, &other);
+            for other in self.opposite_cells[id].clone().iter() {
+                self.make_cells_different(c, &self.cell(other));
             }
         }
     }
@@ -207,15 +171,16 @@ impl InsideTracker {
 
     fn make_cells_same(&mut self, c1: &Coordinate, c2: &Coordinate) {
         if self.is_within_bounds(c1) && self.is_within_bounds(c2) {
-            self.data[c1.0][c1.1].matching_cells.insert(c2.clone());
-            self.data[c2.0][c2.1].matching_cells.insert(c1.clone());
+            let (i1, i2) = (self.id(c1), self.id(c2));
+            self.matching_cells[i1].insert(i2);
+            self.matching_cells[i2].insert(i1);
         }
 
-        if self.is_within_bounds(c1) && self.data[c1.0][c1.1].opposite_cells.contains(c1) {
+        if self.is_within_bounds(c1) && self.opposite_cells[self.id(c1)].contains(self.id(c1)) {
             self.found_contradiction = true;
             return;
         }
-        if self.is_within_bounds(c2) && self.data[c2.0][c2.1].opposite_cells.contains(c2) {
+        if self.is_within_bounds(c2) && self.opposite_cells[self.id(c2)].contains(self.id(c2)) {
             self.found_contradiction = true;
             return;
         }
@@ -236,15 +201,16 @@ impl InsideTracker {
 
     fn make_cells_different(&mut self, c1: &Coordinate, c2: &Coordinate) {
         if self.is_within_bounds(c1) && self.is_within_bounds(c2) {
-            self.data[c1.0][c1.1].opposite_cells.insert(c2.clone());
-            self.data[c2.0][c2.1].opposite_cells.insert(c1.clone());
+            let (i1, i2) = (self.id(c1), self.id(c2));
+            self.opposite_cells[i1].insert(i2);
+            self.opposite_cells[i2].insert(i1);
         }
 
-        if self.is_within_bounds(c1) && self.data[c1.0][c1.1].opposite_cells.contains(c1) {
+        if self.is_within_bounds(c1) && self.opposite_cells[self.id(c1)].contains(self.id(c1)) {
             self.found_contradiction = true;
             return;
         }
-        if self.is_within_bounds(c2) && self.data[c2.0][c2.1].opposite_cells.contains(c2) {
+        if self.is_within_bounds(c2) && self.opposite_cells[self.id(c2)].contains(self.id(c2)) {
             self.found_contradiction = true;
             return;
         }
@@ -347,17 +313,17 @@ impl InsideTracker {
         }
     }
 
-    pub fn non_outside_neighbours(&self, c: &Coordinate) -> HashSet<Coordinate> {
-        let mut result: HashSet<Coordinate> = HashSet::new();
+    pub fn non_outside_neighbours(&self, c: &Coordinate) -> Vec<Coordinate> {
+        let mut result: Vec<Coordinate> = Vec::new();
         let mut n: Coordinate;
         n = Coordinate(c.0.wrapping_sub(1), c.1);
-        if !self.is_outside(&n) { result.insert(n); }
+        if !self.is_outside(&n) { result.push(n); }
         n = Coordinate(c.0 + 1, c.1);
-        if !self.is_outside(&n) { result.insert(n); }
+        if !self.is_outside(&n) { result.push(n); }
         n = Coordinate(c.0, c.1.wrapping_sub(1));
-        if !self.is_outside(&n) { result.insert(n); }
+        if !self.is_outside(&n) { result.push(n); }
         n = Coordinate(c.0, c.1 + 1);
-        if !self.is_outside(&n) { result.insert(n); }
+        if !self.is_outside(&n) { result.push(n); }
         return result;
     }
 
@@ -366,26 +332,22 @@ impl InsideTracker {
             // Vacuously true
             return true;
         }
-        let mut component: HashSet<Coordinate> = HashSet::new();
+        let mut component = BitSet::new(self.width * self.height);
         let mut new_nodes: Vec<Coordinate> = Vec::new();
-        let first = self.inside_cells.iter().next().unwrap();
-        component.insert(first.clone());
-        new_nodes.push(first.clone());
+        let first = self.cell(self.inside_cells.iter().next().unwrap());
+        component.insert(self.id(&first));
+        new_nodes.push(first);
         while !new_nodes.is_empty() {
             let new_node = new_nodes.pop().unwrap();
             for neighbour in self.non_outside_neighbours(&new_node) {
-                if !neighbour.eq(&c) && !component.contains(&neighbour) {
-                    component.insert(neighbour.clone());
+                if !neighbour.eq(c) && component.insert(self.id(&neighbour)) {
                     new_nodes.push(neighbour.clone());
                 }
             }
         }
-        for node in self.inside_cells.iter() {
-            if !component.contains(&node) {
-                return false;
-            }
-        }
-        return true;
+        let mut outside_component = self.inside_cells.clone();
+        outside_component.difference_with(&component);
+        return outside_component.is_empty();
     }
 
     pub fn apply_insides_must_be_connected_arguments(&mut self) {
@@ -400,7 +362,7 @@ impl InsideTracker {
         // (node, parent)
         let mut new_nodes: Vec<(Coordinate, Option<Coordinate>)> = Vec::new();
         let mut count = 1;
-        let first = self.inside_cells.iter().next().unwrap();
+        let first = self.cell(self.inside_cells.iter().next().unwrap());
         new_nodes.push((first.clone(), Option::None));
         while !new_nodes.is_empty() {
             let (node, parent) = new_nodes.pop().unwrap();
@@ -424,7 +386,7 @@ impl InsideTracker {
                 }
             }
         }
-        let mut articulation_points:HashSet<Coordinate> = HashSet::new();
+        let mut articulation_points = BitSet::new(self.width * self.height);
         let mut first_as_parent_count = 0;
         for (_key, value) in &traversal_info {
             if value.1.is_some() {
@@ -434,20 +396,21 @@ impl InsideTracker {
                 } else {
                     let parent_position = traversal_info.get(&parent).unwrap().0;
                     if parent_position == value.2 && self.non_outside_neighbours(&parent).len() > 1 {
-                        articulation_points.insert(parent.clone());
+                        articulation_points.insert(self.id(&parent));
                     }
                 }
             }
         }
         if first_as_parent_count > 1 {
-            articulation_points.insert(first.clone());
+            articulation_points.insert(self.id(&first));
         }
 
         // Look at each articulation point. If it's not set yet and setting it to outside would
         // make it imposible to make a single connected component for the inside cells, then it
         // must be set to inside.
+        let articulation_points: Vec<Coordinate> = articulation_points.iter().map(|i| self.cell(i)).collect();
         for p in articulation_points {
-            if !self.data[p.0][p.1].is_inside && !self.is_inside_connected_without(&p) {
+            if !self.is_inside(&p) && !self.is_inside_connected_without(&p) {
                 self.mark_cell(&p, true);
             }
         }
@@ -5,17 +5,105 @@ use super::direction::HDirection;
 use super::direction::VDirection;
 use super::edge::Edge;
 use super::edge::EdgeType;
+use super::trace::Premise;
+use super::trace::Rule;
+use crate::bitset::BitSet;
 
-use std::collections::HashSet;
-use std::iter::FromIterator;
+// The node next to n in direction d, which has to be inside the grid.
+fn node_towards(n: &Coordinate, d: &Direction) -> Coordinate {
+    return match d {
+        Direction::UP => Coordinate(n.0 - 1, n.1),
+        Direction::DOWN => Coordinate(n.0 + 1, n.1),
+        Direction::LEFT => Coordinate(n.0, n.1 - 1),
+        Direction::RIGHT => Coordinate(n.0, n.1 + 1),
+    };
+}
 
 impl Solver {
+    // Edges are numbered with the horizontal edges first, row by row, then the vertical edges,
+    // row by row.
+    pub fn num_edges(&self) -> usize {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
+        return (h + 1) * w + h * (w + 1);
+    }
+
+    pub fn edge_id(&self, e: &Edge) -> usize {
+        return match e.edge_type {
+            EdgeType::HORIZONTAL => self.h_edge_id(e.row, e.col),
+            EdgeType::VERTICAL => self.v_edge_id(e.row, e.col),
+        };
+    }
+
+    // The edge with the given id, as it is now.
+    pub fn edge(&self, id: usize) -> Edge {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
+        let (edge_type, row, col) = if id < (h + 1) * w {
+            (EdgeType::HORIZONTAL, id / w, id % w)
+        } else {
+            let i = id - (h + 1) * w;
+            (EdgeType::VERTICAL, i / (w + 1), i % (w + 1))
+        };
+        return Edge { is_on: self.on.contains(id), is_off: self.off.contains(id), row, col, edge_type };
+    }
+
+    // The edge above the cell at (row, col), or below the last row.
+    pub fn h_edge_id(&self, row: usize, col: usize) -> usize {
+        return row * self.puzzle.width + col;
+    }
+
+    // The edge left of the cell at (row, col), or right of the last column.
+    pub fn v_edge_id(&self, row: usize, col: usize) -> usize {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
+        return (h + 1) * w + row * (w + 1) + col;
+    }
+
+    pub fn h_edge(&self, row: usize, col: usize) -> Edge {
+        return self.edge(self.h_edge_id(row, col));
+    }
+
+    pub fn v_edge(&self, row: usize, col: usize) -> Edge {
+        return self.edge(self.v_edge_id(row, col));
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        return (self.puzzle.height + 1) * (self.puzzle.width + 1);
+    }
+
+    pub fn node_id(&self, n: &Coordinate) -> usize {
+        return n.0 * (self.puzzle.width + 1) + n.1;
+    }
+
+    pub fn node(&self, id: usize) -> Coordinate {
+        let w = self.puzzle.width + 1;
+        return Coordinate(id / w, id % w);
+    }
+
+    pub fn cell_id(&self, c: &Coordinate) -> usize {
+        return c.0 * self.puzzle.width + c.1;
+    }
+
+    pub fn cell(&self, id: usize) -> Coordinate {
+        let w = self.puzzle.width;
+        return Coordinate(id / w, id % w);
+    }
+
+    // The edge joining two nodes, if they are next to each other.
+    pub fn edge_between(&self, a: &Coordinate, b: &Coordinate) -> Option<Edge> {
+        for d in Direction::iter() {
+            match self.edge_from_node(a, d) {
+                Some(e) if e.touches_node(b) => { return Some(e); },
+                _ => {},
+            }
+        }
+        return None;
+    }
+
     pub fn edge_from_cell(&self, c: &Coordinate, d: &Direction) -> Edge {
         return match d {
-            Direction::UP => self.h_edges[c.0][c.1],
-            Direction::DOWN => self.h_edges[c.0 + 1][c.1],
-            Direction::LEFT => self.v_edges[c.0][c.1],
-            Direction::RIGHT => self.v_edges[c.0][c.1 + 1],
+            Direction::UP => self.h_edge(c.0, c.1),
+            Direction::DOWN => self.h_edge(c.0 + 1, c.1),
+            Direction::LEFT => self.v_edge(c.0, c.1),
+            Direction::RIGHT => self.v_edge(c.0, c.1 + 1),
         }
     }
 
@@ -28,27 +116,32 @@ impl Solver {
         ]
     }
 
-    pub fn edge_from_node(&self, c: &Coordinate, d: &Direction) -> Option<Edge> {
+    pub fn edge_id_from_node(&self, c: &Coordinate, d: &Direction) -> Option<usize> {
+        let (h, w) = (self.puzzle.height, self.puzzle.width);
         return match d {
             Direction::UP => {
                 if c.0 == 0 { Option::None }
-                else { Option::Some(self.v_edges[c.0 - 1][c.1]) }
+                else { Option::Some(self.v_edge_id(c.0 - 1, c.1)) }
             },
             Direction::DOWN => {
-                if c.0 == self.puzzle.size { Option::None }
-                else { Option::Some(self.v_edges[c.0][c.1]) }
+                if c.0 == h { Option::None }
+                else { Option::Some(self.v_edge_id(c.0, c.1)) }
             },
             Direction::LEFT => {
                 if c.1 == 0 { Option::None }
-                else { Option::Some(self.h_edges[c.0][c.1 - 1]) }
+                else { Option::Some(self.h_edge_id(c.0, c.1 - 1)) }
             },
             Direction::RIGHT => {
-                if c.1 == self.puzzle.size { Option::None }
-                else { Option::Some(self.h_edges[c.0][c.1]) }
+                if c.1 == w { Option::None }
+                else { Option::Some(self.h_edge_id(c.0, c.1)) }
             },
         }
     }
 
+    pub fn edge_from_node(&self, c: &Coordinate, d: &Direction) -> Option<Edge> {
+        return self.edge_id_from_node(c, d).map(|id| self.edge(id));
+    }
+
     pub fn edges_from_node(&self, c: &Coordinate) -> [Option<Edge>; 4] {
         return [
             self.edge_from_node(c, &Direction::UP),
@@ -104,7 +197,7 @@ impl Solver {
                     true => Option::None,
                     false => Option::Some(Coordinate(e.row - 1, e.col)),
                 };
-                let b = match e.row == self.puzzle.size {
+                let b = match e.row == self.puzzle.height {
                     true => Option::None,
                     false => Option::Some(Coordinate(e.row, e.col)),
                 };
@@ -115,7 +208,7 @@ impl Solver {
                     true => Option::None,
                     false => Option::Some(Coordinate(e.row, e.col - 1)),
                 };
-                let b = match e.col == self.puzzle.size {
+                let b = match e.col == self.puzzle.width {
                     true => Option::None,
                     false => Option::Some(Coordinate(e.row, e.col)),
                 };
@@ -143,7 +236,7 @@ impl Solver {
                 n.0 - 1
             },
             VDirection::DOWN => {
-                if n.0 == self.puzzle.size { return Option::None; }
+                if n.0 == self.puzzle.height { return Option::None; }
                 n.0
             },
         };
@@ -153,7 +246,7 @@ impl Solver {
                 n.1 - 1
             },
             HDirection::RIGHT => {
-                if n.1 == self.puzzle.size { return Option::None; }
+                if n.1 == self.puzzle.width { return Option::None; }
                 n.1
             },
         };
@@ -176,7 +269,7 @@ impl Solver {
                 n.0 - 1
             },
             VDirection::DOWN => {
-                if n.0 == self.puzzle.size { return Option::None; }
+                if n.0 == self.puzzle.height { return Option::None; }
                 n.0 + 1
             },
         };
@@ -186,7 +279,7 @@ impl Solver {
                 n.1 - 1
             },
             HDirection::RIGHT => {
-                if n.1 == self.puzzle.size { return Option::None; }
+                if n.1 == self.puzzle.width { return Option::None; }
                 n.1 + 1
             },
         };
@@ -200,7 +293,7 @@ impl Solver {
             } else {
                 Option::None
             },
-            Direction::DOWN => if c.0 < self.puzzle.size - 1 {
+            Direction::DOWN => if c.0 < self.puzzle.height - 1 {
                 Option::Some(Coordinate(c.0 + 1, c.1))
             } else {
                 Option::None
@@ -210,7 +303,7 @@ impl Solver {
             } else {
                 Option::None
             },
-            Direction::RIGHT => if c.1 < self.puzzle.size - 1 {
+            Direction::RIGHT => if c.1 < self.puzzle.width - 1 {
                 Option::Some(Coordinate(c.0, c.1 + 1))
             } else {
                 Option::None
@@ -218,139 +311,92 @@ impl Solver {
         };
     }
 
+    // The first of the directions from node n with an edge that isn't off, with that edge's id.
+    fn first_available(&self, n: &Coordinate, directions: [Direction; 3]) -> (usize, Direction) {
+        for d in directions {
+            match self.edge_id_from_node(n, &d) {
+                Some(id) if !self.off.contains(id) => { return (id, d); },
+                _ => {},
+            }
+        }
+        // This should never happen.
+        panic!("Edge with dead end found.");
+    }
+
     pub fn outer_inner_border_argument(&mut self) {
         // This function only works if there are no dead ends.
         // If there are dead ends, return for now and let other inferences remove them first.
         if self.has_dead_end() {
             return;
         }
-        // Find any top most horizontal edge that isn't off yet.
-        let mut e = self.h_edges[0][0];
-        for row in 0..(self.puzzle.size + 1) {
-            for col in 0..self.puzzle.size {
-                e = self.h_edges[row][col];
-                if !e.is_off {
-                    break;
-                }
-            }
-            if !e.is_off {
-                break;
-            }
-        }
-        // If all edges are off, then just return.
-        if e.is_off {
-            return;
-        }
+        // Find any top most horizontal edge that isn't off yet. The horizontal edges come first,
+        // row by row.
+        let first = (0..(self.puzzle.height + 1) * self.puzzle.width).find(|id| !self.off.contains(*id));
+        let e = match first {
+            Some(id) => self.edge(id),
+            // If all edges are off, then just return.
+            None => { return; },
+        };
         // Start by going right and whenever there is a fork, chose a clockwise turn over going
         // straight over a counter clocwise turn.
-        let is_available = |o: Option<Edge>| o.is_some() && !o.unwrap().is_off;
-        let mut border:Vec<Edge> = Vec::new();
-        let mut directions:Vec<Direction> = Vec::new();
-        border.push(e);
+        // Each step of the border is an edge, the direction it is walked in and the node it ends at.
+        let mut border: Vec<(usize, Direction, Coordinate)> = Vec::new();
         let mut d = Direction::RIGHT;
-        directions.push(d);
         let mut n = self.node_from_edge(&e, &d).unwrap();
-        let mut next_e;
-        if is_available(self.edge_from_node(&n, &d.clockwise())) {
-            next_e = self.edge_from_node(&n, &d.clockwise()).unwrap();
-            d = d.clockwise();
-        } else if is_available(self.edge_from_node(&n, &d)) {
-            next_e = self.edge_from_node(&n, &d).unwrap();
-        } else if is_available(self.edge_from_node(&n, &d.counter_clockwise())) {
-            next_e = self.edge_from_node(&n, &d.counter_clockwise()).unwrap();
-            d = d.counter_clockwise();
-        } else {
-            // This should never happen.
-            panic!("Edge with dead end found.");
-        }
-        while !next_e.eq(&border[0]) {
-            border.push(next_e);
-            directions.push(d);
-            e = next_e;
-            n = self.node_from_edge(&e, &d).unwrap();
-            if is_available(self.edge_from_node(&n, &d.clockwise())) {
-                next_e = self.edge_from_node(&n, &d.clockwise()).unwrap();
-                d = d.clockwise();
-            } else if is_available(self.edge_from_node(&n, &d)) {
-                next_e = self.edge_from_node(&n, &d).unwrap();
-            } else if is_available(self.edge_from_node(&n, &d.counter_clockwise())) {
-                next_e = self.edge_from_node(&n, &d.counter_clockwise()).unwrap();
-                d = d.counter_clockwise();
-            } else {
-                // This should never happen.
-                panic!("Edge with dead end found.");
-            }
+        border.push((self.edge_id(&e), d, n));
+        let (mut next_e, mut next_d) = self.first_available(&n, [d.clockwise(), d, d.counter_clockwise()]);
+        while next_e != border[0].0 {
+            d = next_d;
+            n = node_towards(&n, &d);
+            border.push((next_e, d, n));
+            (next_e, next_d) = self.first_available(&n, [d.clockwise(), d, d.counter_clockwise()]);
         }
 
-
-        let mut border_edges: HashSet<Edge> = HashSet::new();
-        for border_edge in border.iter() {
-            if border_edges.contains(border_edge) {
+        let mut border_edges = BitSet::new(self.num_edges());
+        for (border_edge, _, _) in border.iter() {
+            if !border_edges.insert(*border_edge) {
                 // If an edge appear twice on the border, then that edge is a bridge.
                 // That edge must be therefore be off.
-                self.set(border_edge, false);
+                let e = self.edge(*border_edge);
+                self.set(&e, false, Rule::OuterInnerBorder, &[Premise::Edge(e)]);
                 return;
                 // TODO: We could also conclude that if one of the connected components has at
                 // least one edge, then all of the other connected component must be off.
             }
-            border_edges.insert(border_edge.clone());
         }
 
         for i in 0..border.len() {
-            let mut intersection: Vec<Edge> = Vec::new();
-            let mut inner_path: Vec<Edge> = Vec::new();
+            let mut intersection: Vec<usize> = Vec::new();
             let mut has_at_least_one_non_set_intersection = false;
             let mut has_been_out = false;
             let mut has_been_back_in = false;
             let mut has_been_out_again = false;
-            let mut inner_e = border[i];
-            let mut inner_d = directions[i];
-
-            let potential_degree = |c: &Coordinate| {
-                let mut count = 0;
-                for e in self.edges_from_node(c) {
-                    if e.is_some() && !e.unwrap().is_off {
-                        count += 1;
-                    }
-                }
-                return count;
-            };
+            let (mut inner_e, mut inner_d, mut inner_n) = border[i];
 
             let mut first_run = true;
-            while first_run || !inner_e.eq(&intersection[0]) {
+            while first_run || inner_e != intersection[0] {
                 first_run = false;
-                let inner_n = self.node_from_edge(&inner_e, &inner_d).unwrap();
                 if has_been_out == false {
-                    if potential_degree(&inner_n) > 2 {
+                    if self.potential_degree(&inner_n) > 2 {
                         has_been_out = true;
                     }
                 } else if has_been_back_in == false {
-                    if border_edges.contains(&inner_e) {
+                    if border_edges.contains(inner_e) {
                         has_been_back_in = true;
                     }
                 } else {
-                    if potential_degree(&inner_n) > 2 {
+                    if self.potential_degree(&inner_n) > 2 {
                         has_been_out_again = true;
                     }
                 }
-                if border_edges.contains(&inner_e) {
+                if border_edges.contains(inner_e) {
                     intersection.push(inner_e);
-                    if !inner_e.is_on {
+                    if !self.on.contains(inner_e) {
                         has_at_least_one_non_set_intersection = true;
                     }
                 }
-                if is_available(self.edge_from_node(&inner_n, &inner_d.counter_clockwise())) {
-                    inner_e = self.edge_from_node(&inner_n, &inner_d.counter_clockwise()).unwrap();
-                    inner_d = inner_d.counter_clockwise();
-                } else if is_available(self.edge_from_node(&inner_n, &inner_d)) {
-                    inner_e = self.edge_from_node(&inner_n, &inner_d).unwrap();
-                } else if is_available(self.edge_from_node(&inner_n, &inner_d.clockwise())) {
-                    inner_e = self.edge_from_node(&inner_n, &inner_d.clockwise()).unwrap();
-                    inner_d = inner_d.clockwise();
-                } else {
-                    // This should never happen.
-                    panic!("Edge with dead end found.");
-                }
+                (inner_e, inner_d) = self.first_available(&inner_n, [inner_d.counter_clockwise(), inner_d, inner_d.clockwise()]);
+                inner_n = node_towards(&inner_n, &inner_d);
             }
             if has_been_out_again && has_at_least_one_non_set_intersection {
                 // If the inner loop touched the outer loop into two non-consecutive sections, then
@@ -358,7 +404,11 @@ impl Solver {
                 // If there is at leas one edge in both sections, then the two sections must be
                 // connected and they only way to do that is to turn on the intersection.
                 let mut count = 0;
-                let intersection_set: HashSet<Edge> = HashSet::from_iter(intersection.iter().cloned());
+                let mut intersection_set = BitSet::new(self.num_edges());
+                for e in intersection.iter() {
+                    intersection_set.insert(*e);
+                }
+                let intersection: Vec<Edge> = intersection.iter().map(|id| self.edge(*id)).collect();
                 for e in intersection.iter() {
                     if self.connected_component_has_on(&e.nodes()[0], &intersection_set) {
                         count += 1;
@@ -371,8 +421,9 @@ impl Solver {
                     }
                 }
                 if count > 2 {
+                    let premises: Vec<Premise> = intersection.iter().map(|e| Premise::Edge(*e)).collect();
                     for e in intersection.iter() {
-                        self.set(&e, true);
+                        self.set(e, true, Rule::OuterInnerBorder, &premises);
                     }
                     return;
                 }
@@ -380,24 +431,23 @@ impl Solver {
         }
     }
 
-    pub fn connected_component_has_on(&self, start: &Coordinate, edges_to_avoid: &HashSet<Edge>) -> bool {
-        let mut already_added: HashSet<Coordinate> = HashSet::new();
+    pub fn connected_component_has_on(&self, start: &Coordinate, edges_to_avoid: &BitSet) -> bool {
+        let mut already_added = BitSet::new(self.num_nodes());
         let mut to_visit: Vec<Coordinate> = Vec::new();
         to_visit.push(start.clone());
-        already_added.insert(start.clone());
+        already_added.insert(self.node_id(start));
         while !to_visit.is_empty() {
             let n = to_visit.pop().unwrap();
             for edge in self.edges_from_node(&n) {
                 match edge {
                     Some(e) => {
-                        if !e.is_off && !edges_to_avoid.contains(&e) {
+                        if !e.is_off && !edges_to_avoid.contains(self.edge_id(&e)) {
                             if e.is_on {
                                 return true;
                             }
                             let other = e.other_node(&n);
-                            if !already_added.contains(&other) {
+                            if already_added.insert(self.node_id(&other)) {
                                 to_visit.push(other);
-                                already_added.insert(other);
                             }
                         }
                     },
@@ -408,20 +458,24 @@ impl Solver {
         return false;
     }
 
-    pub fn all_in_same_connect_component(&self, mut nodes: HashSet<Coordinate>) -> bool {
+    pub fn all_in_same_connect_component(&self, nodes: &[Coordinate]) -> bool {
         if nodes.is_empty() {
             // Vacuously true
             return true;
         }
-        let mut component: HashSet<Coordinate> = HashSet::new();
+        let mut left = BitSet::new(self.num_nodes());
+        for n in nodes {
+            left.insert(self.node_id(n));
+        }
+        let mut component = BitSet::new(self.num_nodes());
         let mut newly_added: Vec<Coordinate> = Vec::new();
-        let first = nodes.iter().next().unwrap();
+        let first = nodes[0];
         newly_added.push(first.clone());
-        component.insert(first.clone());
+        component.insert(self.node_id(&first));
         while !newly_added.is_empty() {
             let n = newly_added.pop().unwrap();
-            nodes.remove(&n);
-            if nodes.is_empty() {
+            left.remove(self.node_id(&n));
+            if left.is_empty() {
                 return true;
             }
             for edge in self.edges_from_node(&n) {
@@ -429,8 +483,7 @@ impl Solver {
                     Some(e) => {
                         if !e.is_off {
                             let other = e.other_node(&n);
-                            if !component.contains(&other) {
-                                component.insert(other.clone());
+                            if component.insert(self.node_id(&other)) {
                                 newly_added.push(other);
                             }
                         }
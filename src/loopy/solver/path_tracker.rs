@@ -1,109 +1,123 @@
 use super::coordinate::Coordinate;
-use std::collections::HashMap;
-use std::collections::HashSet;
 
 #[derive(Clone, Debug)]
 pub struct PathTracker {
-    endpoints: HashMap<Coordinate, (Coordinate, Coordinate)>,
+    width: usize,
+    // For each node at the end of a path, the node at the other end. Indexed by row * (width + 1)
+    // + col.
+    other_end: Vec<Option<Coordinate>>,
+    // How many edges are on at each node, indexed like other_end.
+    degree: Vec<u8>,
+    num_endpoints: usize,
     num_loops: usize,
+    // Whether a node has more than two edges on. The paths can't be told apart after that, so they
+    // aren't tracked anymore.
+    has_branch: bool,
 }
 
 impl PathTracker {
-    pub fn new() -> PathTracker {
+    pub fn new(width: usize, height: usize) -> PathTracker {
         return PathTracker{
-            endpoints: HashMap::new(),
+            width,
+            other_end: vec![None; (width + 1) * (height + 1)],
+            degree: vec![0; (width + 1) * (height + 1)],
+            num_endpoints: 0,
             num_loops: 0,
+            has_branch: false,
+        }
+    }
+
+    fn index(&self, c: &Coordinate) -> usize {
+        return c.0 * (self.width + 1) + c.1;
+    }
+
+    fn get(&self, c: &Coordinate) -> Option<Coordinate> {
+        return self.other_end[self.index(c)];
+    }
+
+    fn insert(&mut self, c: &Coordinate, other: &Coordinate) {
+        let i = self.index(c);
+        if self.other_end[i].is_none() {
+            self.num_endpoints += 1;
+        }
+        self.other_end[i] = Some(*other);
+    }
+
+    fn remove(&mut self, c: &Coordinate) {
+        let i = self.index(c);
+        if self.other_end[i].take().is_some() {
+            self.num_endpoints -= 1;
         }
     }
 
     pub fn num_paths(&self) -> usize {
-        return self.num_loops + (self.endpoints.len() / 2);
+        return self.num_loops + (self.num_endpoints / 2);
     }
 
     pub fn has_loop(&self) -> bool {
         return self.num_loops > 0;
     }
 
-    pub fn get_endpoints(&self) -> HashSet<Coordinate> {
-        return self.endpoints.keys().cloned().collect();
+    // Whether a node has more than two edges on, which no loop has.
+    pub fn has_branch(&self) -> bool {
+        return self.has_branch;
     }
 
-    pub fn would_create_loop(&mut self, c1: &Coordinate, c2: &Coordinate) -> bool {
-        match self.endpoints.get(c1) {
-            Some(x) => {
-                return (x.0.eq(c1) && x.1.eq(c2)) || (x.0.eq(c2) && x.1.eq(c1));
-            },
-            None => {
-                return false;
-            },
-        }
+    // The ends of every path, in row major order.
+    pub fn get_endpoints(&self) -> Vec<Coordinate> {
+        let w = self.width + 1;
+        return (0..self.other_end.len()).filter(|i| self.other_end[*i].is_some()).map(|i| Coordinate(i / w, i % w)).collect();
     }
 
     // Returns the endpoints of the path the edge was added to.
-    // Returns None if the edge forms a loop (and therefore doesn't have endponts)
+    // Returns None if the edge forms a loop (and therefore doesn't have endponts), or if it makes a
+    // branch.
     pub fn add_edge(&mut self, c1: &Coordinate, c2: &Coordinate) -> Option<(Coordinate, Coordinate)> {
+        let (i1, i2) = (self.index(c1), self.index(c2));
+        self.degree[i1] += 1;
+        self.degree[i2] += 1;
+        // A node in the middle of a path isn't an endpoint, so without this the edge would start a
+        // new path there.
+        if self.has_branch || self.degree[i1] > 2 || self.degree[i2] > 2 {
+            self.has_branch = true;
+            return Option::None;
+        }
         let new_path: (Coordinate, Coordinate);
-        let p1 = self.endpoints.get(c1);
-        let p2 = self.endpoints.get(c2);
-        if p1.is_none() && p2.is_none() {
-            // Start a new path
-            new_path = (c1.clone(), c2.clone());
-            self.endpoints.insert(c1.clone(), new_path);
-            self.endpoints.insert(c2.clone(), new_path);
-        } else if p1.is_some() && p2.is_none() {
-            // Update p1 to include c2
-            let old_path = p1.unwrap();
-            let other;
-            match old_path.0.eq(c1) {
-                true => {
-                    other = old_path.1;
-                    new_path = (c2.clone(), other.clone());
-                },
-                false => {
-                    other = old_path.0;
-                    new_path = (other.clone(), c2.clone());
-                },
-            };
-            self.endpoints.remove(c1);
-            self.endpoints.insert(c2.clone(), new_path);
-            self.endpoints.insert(other.clone(), new_path);
-        } else if p1.is_none() && p2.is_some() {
-            // Update p2 to include c1
-            let old_path = p2.unwrap();
-            let other;
-            match old_path.0.eq(c2) {
-                    true => {
-                        other = old_path.1;
-                        new_path = (c1.clone(), other.clone());
-                    },
-                    false => {
-                        other = old_path.0;
-                        new_path = (other.clone(), c1.clone());
-                    },
-            };
-            self.endpoints.remove(c2);
-            self.endpoints.insert(c1.clone(), new_path);
-            self.endpoints.insert(other.clone(), new_path);
-        } else {
-            // Joining two paths together
-            let other1 = match p1.unwrap().0.eq(c1) {
-                true => p1.unwrap().1,
-                false => p1.unwrap().0,
-            };
-            let other2 = match p2.unwrap().0.eq(c2) {
-                true => p2.unwrap().1,
-                false => p2.unwrap().0,
-            };
-            self.endpoints.remove(c1);
-            self.endpoints.remove(c2);
-            if (other1.eq(c2) && other2.eq(c1)) || (other1.eq(c1) && other2.eq(c2)) {
-                self.num_loops += 1;
-                return Option::None
-            } else {
+        let p1 = self.get(c1);
+        let p2 = self.get(c2);
+        match (p1, p2) {
+            (None, None) => {
+                // Start a new path
+                new_path = (*c1, *c2);
+                self.insert(c1, c2);
+                self.insert(c2, c1);
+            },
+            (Some(other), None) => {
+                // Update p1 to include c2
+                new_path = (*c2, other);
+                self.remove(c1);
+                self.insert(c2, &other);
+                self.insert(&other, c2);
+            },
+            (None, Some(other)) => {
+                // Update p2 to include c1
+                new_path = (*c1, other);
+                self.remove(c2);
+                self.insert(c1, &other);
+                self.insert(&other, c1);
+            },
+            (Some(other1), Some(other2)) => {
+                // Joining two paths together
+                self.remove(c1);
+                self.remove(c2);
+                if other1.eq(c2) && other2.eq(c1) {
+                    self.num_loops += 1;
+                    return Option::None
+                }
                 new_path = (other1, other2);
-                self.endpoints.insert(other1, new_path);
-                self.endpoints.insert(other2, new_path);
-            }
+                self.insert(&other1, &other2);
+                self.insert(&other2, &other1);
+            },
         }
         return Option::Some(new_path);
     }
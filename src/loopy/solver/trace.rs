@@ -0,0 +1,127 @@
+use super::coordinate::Coordinate;
+use super::edge::Edge;
+use super::edge::EdgeType;
+use crate::grader::Technique;
+use crate::grader::Tier;
+
+use std::collections::HashMap;
+
+// The rules that decide edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    // hint_analysis: every edge of a 0 is off.
+    ZeroHint,
+    // hint_analysis: every edge of a 4 is on.
+    FourHint,
+    // handle_adjacent_3: two 3s side by side.
+    AdjacentThrees,
+    // handle_diagonal_3: two 3s on a diagonal, with only 2s in between.
+    DiagonalThrees,
+    // handle_3_next_to_1_on_edge: a 3 next to a 1 on the border of the grid.
+    ThreeNextToOneOnBorder,
+    // A hint has as many on edges as it needs, or as many on and unknown edges as it needs.
+    CellCount,
+    // A node has two on edges, or only one way left to continue or end a path.
+    NodeDegree,
+    // An edge joining the two ends of a path would close a loop too early.
+    LoopClosing,
+    // A corner of a cell is an entry: exactly one of the cell's edges at that corner is on.
+    CornerEntry,
+    // A corner of a cell isn't an entry: both or neither of the cell's edges at that corner are on.
+    CornerNoEntry,
+    // A path touches a 2 or a 3 at a corner from the outside.
+    CornerTouch,
+    // A cell with at most one open corner is either entirely in the loop or entirely out of it.
+    ClosedCorners,
+    // outer_inner_border_argument: an edge is a bridge or joins two parts that both have edges.
+    OuterInnerBorder,
+    // The edge separates a cell known to be inside the loop from one known to be outside (on),
+    // or two cells on the same side (off).
+    InsideOutside,
+    // A 1 or a 3 between cells on the same side of the loop.
+    HintBetweenSimilarCells,
+    // The inside of the loop has to be connected.
+    InsidesConnected,
+    // A value tried by the depth solver to see where it leads.
+    Guess,
+    // Guessing the opposite value leads to a puzzle without solutions.
+    TrialAndError,
+}
+
+impl Technique for Rule {
+    fn tier(&self) -> Tier {
+        return match self {
+            Rule::ZeroHint | Rule::FourHint | Rule::CellCount | Rule::NodeDegree => Tier::Easy,
+            Rule::AdjacentThrees | Rule::DiagonalThrees | Rule::ThreeNextToOneOnBorder => Tier::Normal,
+            Rule::LoopClosing | Rule::InsideOutside => Tier::Normal,
+            Rule::CornerEntry | Rule::CornerNoEntry | Rule::CornerTouch => Tier::Normal,
+            Rule::ClosedCorners | Rule::HintBetweenSimilarCells => Tier::Hard,
+            Rule::OuterInnerBorder | Rule::InsidesConnected => Tier::Extreme,
+            Rule::Guess | Rule::TrialAndError => Tier::Unreasonable,
+        };
+    }
+}
+
+// Something a rule looked at to decide an edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Premise {
+    // A cell and its hint, if any.
+    Cell(Coordinate),
+    // A node and the edges that touch it.
+    Node(Coordinate),
+    // An edge, as it was when the rule used it.
+    Edge(Edge),
+}
+
+// One edge being decided.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    // Which edge was decided. Its is_on and is_off fields are not meaningful.
+    pub edge: Edge,
+    pub on: bool,
+    pub rule: Rule,
+    pub premises: Vec<Premise>,
+}
+
+// The state of every edge part way through a trace, None for edges that aren't decided yet.
+// Indexed like Solution: horizontal is (height + 1) by width, vertical is height by (width + 1).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Board {
+    pub horizontal: Vec<Vec<Option<bool>>>,
+    pub vertical: Vec<Vec<Option<bool>>>,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Board {
+        return Board {
+            horizontal: vec![vec![None; width]; height + 1],
+            vertical: vec![vec![None; width + 1]; height],
+        };
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        let e = &step.edge;
+        match e.edge_type {
+            EdgeType::HORIZONTAL => { self.horizontal[e.row][e.col] = Some(step.on); },
+            EdgeType::VERTICAL => { self.vertical[e.row][e.col] = Some(step.on); },
+        }
+    }
+}
+
+// The board after the given steps, starting from a board where nothing is decided.
+pub fn replay(width: usize, height: usize, steps: &[Step]) -> Board {
+    let mut board = Board::new(width, height);
+    for step in steps {
+        board.apply(step);
+    }
+    return board;
+}
+
+// How many edges each rule decided.
+pub fn count_by_rule(steps: &[Step]) -> HashMap<Rule, usize> {
+    let mut counts = HashMap::new();
+    for step in steps {
+        *counts.entry(step.rule).or_insert(0) += 1;
+    }
+    return counts;
+}
@@ -0,0 +1,110 @@
+// Checking a candidate loop against a puzzle without the solver, and saying which rule it breaks.
+
+use super::puzzle::Puzzle;
+use super::puzzle::Solution;
+
+use std::fmt;
+
+// The first rule a candidate solution was found to break.
+// Rows and columns of cells and nodes are 0-indexed, but displayed 1-indexed. Nodes are the
+// corners of the cells, so there is one more row and one more column of them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    // The edge matrices don't have the shape of a puzzle of that width and height.
+    WrongSize { width: usize, height: usize },
+    // The cell's hint doesn't match the number of its sides that are on the loop.
+    CellCount { row: usize, column: usize, hint: u8, count: u8 },
+    // A single edge of the candidate ends at the node.
+    DeadEnd { row: usize, column: usize },
+    // Three or four edges of the candidate meet at the node.
+    Branch { row: usize, column: usize, edges: u8 },
+    // The candidate has no edges at all.
+    NoLoop,
+    // The edges form several separate loops instead of one.
+    SeveralLoops { count: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Violation::WrongSize { width, height } => {
+                write!(f, "the edges don't fit a {} by {} grid", width, height)
+            },
+            Violation::CellCount { row, column, hint, count } => {
+                write!(f, "cell ({}, {}) has {} sides on the loop instead of {}", row + 1, column + 1, count, hint)
+            },
+            Violation::DeadEnd { row, column } => {
+                write!(f, "the line stops at node ({}, {})", row + 1, column + 1)
+            },
+            Violation::Branch { row, column, edges } => {
+                write!(f, "{} lines meet at node ({}, {})", edges, row + 1, column + 1)
+            },
+            Violation::NoLoop => write!(f, "there is no loop"),
+            Violation::SeveralLoops { count } => write!(f, "there are {} loops instead of one", count),
+        };
+    }
+}
+
+// Checks that every hint has the right number of sides on the loop and that the edges form
+// exactly one loop.
+pub fn verify(puzzle: &Puzzle, candidate: &Solution) -> Result<(), Violation> {
+    let (height, width) = (puzzle.height, puzzle.width);
+    let wrong_size = Violation::WrongSize { width, height };
+    if candidate.horizontal.len() != height + 1 || candidate.horizontal.iter().any(|r| r.len() != width) {
+        return Err(wrong_size);
+    }
+    if candidate.vertical.len() != height || candidate.vertical.iter().any(|r| r.len() != width + 1) {
+        return Err(wrong_size);
+    }
+    let h = |i: usize, j: usize| candidate.horizontal[i][j];
+    let v = |i: usize, j: usize| candidate.vertical[i][j];
+
+    for i in 0..height {
+        for j in 0..width {
+            if let Some(hint) = puzzle.grid[i][j] {
+                let count = [h(i, j), h(i + 1, j), v(i, j), v(i, j + 1)].iter().filter(|e| **e).count() as u8;
+                if count != hint {
+                    return Err(Violation::CellCount { row: i, column: j, hint, count });
+                }
+            }
+        }
+    }
+
+    // Every node is either off the loop or has exactly two of its edges on it.
+    let mut on_loop: Vec<(usize, usize)> = Vec::new();
+    for i in 0..(height + 1) {
+        for j in 0..(width + 1) {
+            match candidate.neighbours(i, j).len() {
+                0 => {},
+                1 => { return Err(Violation::DeadEnd { row: i, column: j }); },
+                2 => { on_loop.push((i, j)); },
+                x => { return Err(Violation::Branch { row: i, column: j, edges: x as u8 }); },
+            }
+        }
+    }
+    if on_loop.is_empty() {
+        return Err(Violation::NoLoop);
+    }
+
+    // So the edges are made of separate loops. Follow each one to count them.
+    let mut visited = vec![vec![false; width + 1]; height + 1];
+    let mut count = 0;
+    for start in on_loop {
+        if visited[start.0][start.1] {
+            continue;
+        }
+        count += 1;
+        let mut current = start;
+        loop {
+            visited[current.0][current.1] = true;
+            match candidate.neighbours(current.0, current.1).into_iter().find(|x| !visited[x.0][x.1]) {
+                Some(x) => { current = x; },
+                None => break,
+            }
+        }
+    }
+    if count > 1 {
+        return Err(Violation::SeveralLoops { count });
+    }
+    return Ok(());
+}
@@ -1,202 +1,463 @@
-mod loopy;
-mod towers;
-mod latin;
-mod perm;
+use puzzle_solvers::corpus;
+use puzzle_solvers::grader::Tier;
+use puzzle_solvers::latin;
+use puzzle_solvers::latin::maker::Backend;
+use puzzle_solvers::loopy;
+use puzzle_solvers::maker;
+use puzzle_solvers::maker::Progress;
+use puzzle_solvers::puzzle::Puzzle;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::towers;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use std::fs::File;
+use std::io::Read;
 use std::io::Write;
+use std::ops::RangeInclusive;
+use std::process;
 
-fn main() {
-    let p = latin::puzzle::Puzzle::from_tatham_string("12:12c6b1c5c1c3a8b10_11_6a5_2_1d8_11a7_5_9a12a4c9a12_7a11_2b3c9_10b7b11d8d5b2_9_6_6b8b4_12a9_11b3_5_6a10a4_7b2b9_3d1_7_4_12_3_2_11b1a7a6e2_3e6_7");
-    println!("Solving puzzle: ");
-    let mut s = latin::solver::Solver::new(p);
-    println!("{}", s.to_string());
-    let sols = s.full_solve(0, true);
-    if sols.len() == 1 {
-        println!("{}", sols[0].to_string());
+const USAGE: &str = "\
+Usage: puzzle_solvers <COMMAND> [OPTIONS] [FILE]
+
+Commands:
+    solve       Solve the puzzles in FILE (or stdin)
+    generate    Generate new puzzles
+    grade       Report how hard the puzzles in FILE (or stdin) are
+    convert     Rewrite the puzzles in FILE (or stdin) in another format
+
+Options:
+    -t, --type <latin|towers|loopy>     Puzzle family (default: latin)
+    -s, --sizes <N|A..B|A..=B>          Sizes to generate (default: 4)
+    -n, --count <N>                     Number of puzzles to generate per size (default: 1)
+    -d, --min-difficulty <N>            Regenerate puzzles easier than this (default: 0)
+    -g, --tiers <T|A..=B>               Only generate puzzles graded in these tiers, from
+                                        easy, normal, hard, extreme and unreasonable
+    -a, --attempts <N>                  Give up after N rejected attempts at the same puzzle
+                                        (default: 100000)
+    -r, --seed <N>                      Seed of the first puzzle to generate (default: random).
+                                        Each puzzle is written with its seed; generating with
+                                        that seed and the same options gives the same puzzle.
+    -u, --uniform                       Start latin and towers puzzles from a uniformly random
+                                        latin square, which is slower
+    -f, --format <tatham|human>         Output format for convert (default: tatham)
+    -o, --output <PATH>                 Write to PATH instead of stdout. A {size} in PATH is
+                                        replaced by the size of the puzzle being generated.
+    -p, --parallel-depth <N>            Solve and grade by exploring the guesses of the first N
+                                        levels on their own threads (default: 0)
+    -q, --quiet                         Only print results
+    -v, --verbose                       Also print the solvers' progress
+    -h, --help                          Print this message
+";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Solve,
+    Generate,
+    Grade,
+    Convert,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PuzzleType {
+    Latin,
+    Towers,
+    Loopy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Tatham,
+    Human,
+}
+
+#[derive(Clone, Debug)]
+struct Options {
+    command: Command,
+    puzzle_type: PuzzleType,
+    // Inclusive range of sizes.
+    sizes: (usize, usize),
+    count: usize,
+    min_difficulty: u8,
+    // Inclusive range of grades to generate, or None for any grade.
+    tiers: Option<RangeInclusive<Tier>>,
+    // How many times a puzzle can be rejected before generate gives up.
+    max_attempts: usize,
+    seed: Option<u64>,
+    backend: Backend,
+    parallel_depth: u8,
+    format: Format,
+    output: Option<String>,
+    input: Option<String>,
+    // 0 = quiet, 1 = normal, 2 = verbose
+    verbosity: u8,
+}
+
+fn parse_sizes(s: &str) -> Result<(usize, usize), String> {
+    let parse = |x: &str| x.trim().parse::<usize>().map_err(|_| format!("Invalid size: {}", x));
+    let (min, max) = if let Some((a, b)) = s.split_once("..=") {
+        (parse(a)?, parse(b)?)
+    } else if let Some((a, b)) = s.split_once("..") {
+        let b = parse(b)?;
+        if b == 0 {
+            return Err(format!("Empty size range: {}", s));
+        }
+        (parse(a)?, b - 1)
     } else {
-        println!("{}", s.to_string());
+        (parse(s)?, parse(s)?)
+    };
+    if min == 0 || min > max {
+        return Err(format!("Empty size range: {}", s));
     }
+    return Ok((min, max));
+}
 
-    let p_type = "latin";
-    let size_range = 12..13;
-    let quantity = 1;
-    let min_difficulty = 1;
-
-    if p_type.eq("latin") {
-        println!("Generating {} puzzles", p_type);
-        for n in size_range {
-            let mut file = std::fs::File::create(format!("{}/{}_{}.txt", p_type, p_type, n)).expect("create failed");
-            for i in 0..quantity {
-                println!("{}", i);
-                let mut p = latin::maker::make_puzzle(n);
-                while p.difficulty < min_difficulty {
-                    println!("Generated puzzle too easy...");
-                    p = latin::maker::make_puzzle(n);
+fn parse_tiers(s: &str) -> Result<RangeInclusive<Tier>, String> {
+    let parse = |x: &str| x.trim().parse::<Tier>();
+    let (min, max) = match s.split_once("..=") {
+        Some((a, b)) => (parse(a)?, parse(b)?),
+        None => (parse(s)?, parse(s)?),
+    };
+    if min > max {
+        return Err(format!("Empty tier range: {}", s));
+    }
+    return Ok(min..=max);
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut iter = args.iter();
+    let command = match iter.next().map(|x| x.as_str()) {
+        Some("solve") => Command::Solve,
+        Some("generate") => Command::Generate,
+        Some("grade") => Command::Grade,
+        Some("convert") => Command::Convert,
+        Some(x) => { return Err(format!("Unknown command: {}", x)); },
+        None => { return Err(String::from("Missing command")); },
+    };
+
+    let mut options = Options {
+        command,
+        puzzle_type: PuzzleType::Latin,
+        sizes: (4, 4),
+        count: 1,
+        min_difficulty: 0,
+        tiers: None,
+        max_attempts: 100000,
+        seed: None,
+        backend: Backend::RowByRow,
+        parallel_depth: 0,
+        format: Format::Tatham,
+        output: None,
+        input: None,
+        verbosity: 1,
+    };
+
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| match iter.next() {
+            Some(x) => Ok(x.clone()),
+            None => Err(format!("Missing value for {}", name)),
+        };
+        match arg.as_str() {
+            "-t" | "--type" => {
+                options.puzzle_type = match value(arg)?.as_str() {
+                    "latin" => PuzzleType::Latin,
+                    "towers" => PuzzleType::Towers,
+                    "loopy" => PuzzleType::Loopy,
+                    x => { return Err(format!("Unknown puzzle type: {}", x)); },
+                };
+            },
+            "-s" | "--sizes" => { options.sizes = parse_sizes(&value(arg)?)?; },
+            "-n" | "--count" => {
+                let x = value(arg)?;
+                options.count = x.parse().map_err(|_| format!("Invalid count: {}", x))?;
+            },
+            "-d" | "--min-difficulty" => {
+                let x = value(arg)?;
+                options.min_difficulty = x.parse().map_err(|_| format!("Invalid difficulty: {}", x))?;
+            },
+            "-g" | "--tiers" => { options.tiers = Some(parse_tiers(&value(arg)?)?); },
+            "-a" | "--attempts" => {
+                let x = value(arg)?;
+                options.max_attempts = x.parse().map_err(|_| format!("Invalid number of attempts: {}", x))?;
+            },
+            "-r" | "--seed" => {
+                let x = value(arg)?;
+                options.seed = Some(x.parse().map_err(|_| format!("Invalid seed: {}", x))?);
+            },
+            "-u" | "--uniform" => { options.backend = Backend::Uniform; },
+            "-f" | "--format" => {
+                options.format = match value(arg)?.as_str() {
+                    "tatham" => Format::Tatham,
+                    "human" => Format::Human,
+                    x => { return Err(format!("Unknown format: {}", x)); },
+                };
+            },
+            "-o" | "--output" => { options.output = Some(value(arg)?); },
+            "-p" | "--parallel-depth" => {
+                let x = value(arg)?;
+                options.parallel_depth = x.parse().map_err(|_| format!("Invalid depth: {}", x))?;
+            },
+            "-q" | "--quiet" => { options.verbosity = 0; },
+            "-v" | "--verbose" => { options.verbosity = 2; },
+            x if x.starts_with('-') && x != "-" => { return Err(format!("Unknown option: {}", x)); },
+            x => {
+                if options.input.is_some() {
+                    return Err(format!("Unexpected argument: {}", x));
                 }
-                file.write_all((p.difficulty.to_string() + "\n").as_bytes()).expect("write failed");
-                file.write_all((p.to_tatham_string() + "\n\n").as_bytes()).expect("write failed");
+                options.input = Some(String::from(x));
+            },
+        }
+    }
+
+    return Ok(options);
+}
+
+fn read_input(options: &Options) -> Result<String, String> {
+    let mut content = String::new();
+    match options.input.as_deref() {
+        None | Some("-") => {
+            std::io::stdin().read_to_string(&mut content).map_err(|e| format!("Could not read stdin: {}", e))?;
+        },
+        Some(path) => {
+            content = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        },
+    }
+    return Ok(content);
+}
+
+// Keeps track of where results should be written to, opening a new file whenever the output path
+// depends on the size of the puzzle.
+struct Output {
+    template: Option<String>,
+    current_path: Option<String>,
+    writer: Box<dyn Write>,
+}
+
+impl Output {
+    fn new(template: &Option<String>) -> Output {
+        return Output {
+            template: template.clone(),
+            current_path: None,
+            writer: Box::new(std::io::stdout()),
+        };
+    }
+
+    fn write(&mut self, size: usize, s: &str) -> Result<(), String> {
+        if let Some(template) = &self.template {
+            let path = template.replace("{size}", &size.to_string());
+            if self.current_path.as_ref() != Some(&path) {
+                let file = File::create(&path).map_err(|e| format!("Could not create {}: {}", path, e))?;
+                self.writer = Box::new(file);
+                self.current_path = Some(path);
             }
-            println!("Data written to {}/{}_{}.txt", p_type, p_type, n);
         }
+        return self.writer.write_all(s.as_bytes()).map_err(|e| format!("Write failed: {}", e));
     }
+}
 
-    // for n in 2..10 {
-    //     let mut file = std::fs::File::create(format!("loopy/loopy_{}.txt", n)).expect("create failed");
-    //     for i in 0..100 {
-    //         println!("{}", i);
-    //         let p = loopy::maker::make_puzzle(n);
-    //         file.write_all("Difficulty: ".as_bytes()).expect("write failed");
-    //         file.write_all(p.difficulty.to_string().as_bytes()).expect("write failed");
-    //         file.write_all("\n".as_bytes()).expect("write failed");
-    //         file.write_all(p.to_string().as_bytes()).expect("write failed");
-    //         file.write_all("\n-----\n".as_bytes()).expect("write failed");
-    //     }
-    //     println!("Data written to loopy/loopy_{}.txt", n);
-    // }
-
-    // let p = loopy::puzzle::Puzzle::from_string(" \
-    // 2.....20
-    // .22.....
-    // .1..11.3
-    // .1...1..
-    // 2....2..
-    // .1.1...0
-    // .0..231.
-    // ..3...2.
-    // ");
-    //
-    // let mut s = loopy::solver::Solver::new(p);
-    // println!("START!");
-    // println!("{}", s.to_string());
-    // println!("");
-    //
-    // let sol = s.full_solve(0, true);
-    // if sol.len() == 1 {
-    //     println!("{}", sol[0].to_string());
-    // } else {
-    //     println!("{}", s.to_string());
-    // }
-}
-
-// ········
-// ········
-// ·0······
-// ······2·
-// ···20··0
-// ····20··
-// ·····2·3
-// ···2····
-
-// ..33.3.1.22..3..23.3
-// 3.2.1.3..23.3.22.2..
-// 3...3.2.22.....21.32
-// ...2....221..2...22.
-// 13...132.32....0.21.
-// 32...2.2....2..322.2
-// .3.2322....122..1...
-// .21112222..1..33..2.
-// ....221.21.2.1....3.
-// ..2.21.3.....12.213.
-// 22.33..2.1...212...2
-// ...2...2...2.3.22...
-// 232...23..2213......
-// ..21.20....3.322222.
-// 231....312..2111.323
-// ......1..312.33....2
-// 3..22.1323.....122..
-// ..3222.2..212.3..2.1
-// 33.2....3....2....2.
-// .........23...33.213
-
-// ···3······
-// ···1····23
-// ·········3
-// ·3··0·····
-// ··········
-// ····2·1··0
-// ·····2·1··
-// ·11··3·0··
-// ······1··2
-// ······0···
-
-// ······
-// ·····1
-// ······
-// ····0·
-// ····1·
-// ···0··
-
-
-// 0······
-// ·······
-// ·····0·
-// ·······
-// ··2·0·1
-// ····0·3
-// ·······
-
-
-// ·····2·2
-// ·······3
-// ········
-// ·······0
-// ·2······
-// ···3···2
-// 00···3··
-// ·1·2··0·
-
-
-// ·······0
-// ········
-// ········
-// ········
-// ·3·0·0··
-// ····2··1
-// ·3···2··
-// 3····2··
-
-// ··21·2·
-// ·······
-// ·······
-// ····0··
-// 0·····0
-// ····3·1
-// ···1·1·
-
-// 3······
-// ·······
-// ···3···
-// ·······
-// 2····10
-// ···3···
-// ····01·
-
-// ······
-// ······
-// ···0··
-// ······
-// ···0··
-// 2·2···
-
-// ···0···
-// ··0··2·
-// ·······
-// ···3·01
-// ·······
-// ···33··
-// ·222··3
-
-// ··1····
-// ·······
-// ······1
-// ·······
-// 123···3
-// 21·0·23
-// 2······
-
-// ······0
-// 2······
-// ······0
-// ·······
-// ·2··11·
-// ···0·1·
-// ···0···
+fn log(options: &Options, message: &str) {
+    if options.verbosity > 0 {
+        eprintln!("{}", message);
+    }
+}
+
+fn verdict(number_of_solutions: usize) -> &'static str {
+    return match number_of_solutions {
+        0 => "No solution",
+        1 => "Unique solution",
+        _ => "Multiple solutions",
+    };
+}
+
+// A malformed puzzle is reported and skipped instead of stopping the whole batch.
+fn parse_entry<P: Puzzle>(index: usize, entry: &corpus::Entry) -> Option<P> {
+    return match P::from_string(&entry.body) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            eprintln!("Skipping puzzle {}: {}", index + 1, e);
+            None
+        },
+    };
+}
+
+fn solve<S: PuzzleSolver>(options: &Options, entries: &[corpus::Entry], output: &mut Output) -> Result<(), String> {
+    let should_log = options.verbosity > 1;
+    for (i, entry) in entries.iter().enumerate() {
+        log(options, &format!("Solving puzzle {} of {}", i + 1, entries.len()));
+        let Some(p) = parse_entry::<S::Puzzle>(i, entry) else { continue; };
+        let n = p.size();
+        let mut s = S::new(p);
+        if options.parallel_depth > 0 {
+            s.search_in_parallel(options.parallel_depth);
+        }
+        let solutions = s.full_solve(0, should_log);
+        let grid = if solutions.len() == 1 { solutions[0].to_string() } else { s.to_string() };
+        output.write(n, &format!("{}\n{}\n\n", verdict(solutions.len()), grid))?;
+    }
+    return Ok(());
+}
+
+fn grade<S: PuzzleSolver>(options: &Options, entries: &[corpus::Entry], output: &mut Output) -> Result<(), String> {
+    for (i, entry) in entries.iter().enumerate() {
+        let Some(p) = parse_entry::<S::Puzzle>(i, entry) else { continue; };
+        let n = p.size();
+        let mut s = S::new(p);
+        if options.parallel_depth > 0 {
+            s.search_in_parallel(options.parallel_depth);
+        }
+        let solutions = s.full_solve(0, false);
+        let line = match solutions.len() {
+            1 => format!("{}, Difficulty: {}, Grade: {}\n", verdict(1), s.depth_needed(), solutions[0].grade()),
+            x => format!("{}, Difficulty: {}\n", verdict(x), s.depth_needed()),
+        };
+        output.write(n, &line)?;
+    }
+    return Ok(());
+}
+
+fn convert(options: &Options, entries: &[corpus::Entry], output: &mut Output) -> Result<(), String> {
+    for (i, entry) in entries.iter().enumerate() {
+        let difficulty = entry.difficulty.unwrap_or(0);
+        // Only the human format has room to keep a difficulty marked as varying.
+        let human_difficulty = match entry.difficulty_varies {
+            true => format!("{} (varies)", difficulty),
+            false => difficulty.to_string(),
+        };
+        let (size, result) = match (options.puzzle_type, options.format) {
+            (PuzzleType::Latin, Format::Tatham) => {
+                let Some(p) = parse_entry::<latin::puzzle::Puzzle>(i, entry) else { continue; };
+                (p.size(), format!("{}\n{}\n\n", difficulty, p.to_tatham_string()))
+            },
+            (PuzzleType::Latin, Format::Human) => {
+                let Some(p) = parse_entry::<latin::puzzle::Puzzle>(i, entry) else { continue; };
+                (p.size(), format!("Difficulty: {}\n{}\n-----\n", human_difficulty, p.to_human_string()))
+            },
+            (PuzzleType::Towers, Format::Tatham) => {
+                let Some(p) = parse_entry::<towers::puzzle::Puzzle>(i, entry) else { continue; };
+                (p.size(), format!("{}\n{}\n\n", difficulty, p.to_tatham_string()))
+            },
+            (PuzzleType::Towers, Format::Human) => {
+                let Some(p) = parse_entry::<towers::puzzle::Puzzle>(i, entry) else { continue; };
+                (p.size(), format!("Difficulty: {}\n{}\n-----\n", human_difficulty, p.to_human_string()))
+            },
+            (PuzzleType::Loopy, Format::Tatham) => {
+                let Some(p) = parse_entry::<loopy::puzzle::Puzzle>(i, entry) else { continue; };
+                (p.size(), format!("{}\n{}\n\n", difficulty, p.to_tatham_string()))
+            },
+            (PuzzleType::Loopy, Format::Human) => {
+                let Some(p) = parse_entry::<loopy::puzzle::Puzzle>(i, entry) else { continue; };
+                (p.size(), format!("Difficulty: {}\n{}\n-----\n", human_difficulty, p.to_string()))
+            },
+        };
+        output.write(size, &result)?;
+    }
+    return Ok(());
+}
+
+fn generate(options: &Options, output: &mut Output) -> Result<(), String> {
+    if options.puzzle_type == PuzzleType::Latin && options.sizes.1 > latin::puzzle::MAX_SIZE {
+        return Err(format!("Latin puzzles can be at most {} wide", latin::puzzle::MAX_SIZE));
+    }
+    return match (options.puzzle_type, &options.tiers) {
+        (PuzzleType::Latin, None) => {
+            generate_with(options, output, |n, rng| Some(latin::maker::make_puzzle(n as u8, options.backend, rng)))
+        },
+        (PuzzleType::Latin, Some(t)) => {
+            generate_with(options, output, |n, rng| latin::maker::make_puzzle_in_tiers(n as u8, options.backend, t, rng))
+        },
+        (PuzzleType::Towers, None) => {
+            generate_with(options, output, |n, rng| Some(towers::maker::make_puzzle(n as u8, options.backend, rng)))
+        },
+        (PuzzleType::Towers, Some(t)) => {
+            generate_with(options, output, |n, rng| towers::maker::make_puzzle_in_tiers(n as u8, options.backend, t, rng))
+        },
+        (PuzzleType::Loopy, None) => {
+            generate_with(options, output, |n, rng| Some(loopy::maker::make_puzzle(n, n, rng)))
+        },
+        (PuzzleType::Loopy, Some(t)) => {
+            generate_with(options, output, |n, rng| loopy::maker::make_puzzle_in_tiers(n, n, t, rng))
+        },
+    };
+}
+
+// make returns None when an attempt didn't end up in the requested tiers.
+// Puzzles are seeded one after the other starting from options.seed, and each one is made by a
+// generator seeded with its own seed, so it can be made again on its own.
+fn generate_with<P: Puzzle + Send>(
+    options: &Options, output: &mut Output, make: impl Fn(usize, &mut StdRng) -> Option<P> + Sync,
+) -> Result<(), String> {
+    let mut seed = match options.seed {
+        Some(x) => x,
+        None => rand::thread_rng().gen(),
+    };
+    for n in options.sizes.0..(options.sizes.1 + 1) {
+        log(options, &format!("Generating {} {:?} puzzles of size {}", options.count, options.puzzle_type, n));
+        let attempt = |rng: &mut StdRng| match make(n, rng) {
+            Some(p) if p.difficulty() >= options.min_difficulty => Ok(p),
+            Some(_) => Err(String::from("too easy")),
+            None => Err(String::from("outside the requested tiers")),
+        };
+        let report = |progress: Progress| match progress {
+            Progress::Made { seed, made, total, .. } => {
+                log(options, &format!("Puzzle {} of {} done, seed {}", made, total, seed));
+            },
+            Progress::Rejected { seed, reason, .. } => {
+                log(options, &format!("Puzzle with seed {} was {}, retrying...", seed, reason));
+            },
+        };
+        for (seed, p) in maker::make_batch(options.count, seed, options.max_attempts, attempt, report)? {
+            output.write(n, &format!("Difficulty: {}\nSeed: {}\n{}\n-----\n", p.difficulty(), seed, p.to_string()))?;
+        }
+        seed = seed.wrapping_add(options.count as u64);
+    }
+    return Ok(());
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut output = Output::new(&options.output);
+    if options.command == Command::Generate {
+        return generate(options, &mut output);
+    }
+    let entries = corpus::read_entries(&read_input(options)?);
+    return match (options.command, options.puzzle_type) {
+        (Command::Solve, PuzzleType::Latin) => {
+            solve::<latin::solver::Solver>(options, &entries, &mut output)
+        },
+        (Command::Solve, PuzzleType::Towers) => {
+            solve::<towers::solver::Solver>(options, &entries, &mut output)
+        },
+        (Command::Solve, PuzzleType::Loopy) => {
+            solve::<loopy::solver::Solver>(options, &entries, &mut output)
+        },
+        (Command::Grade, PuzzleType::Latin) => {
+            grade::<latin::solver::Solver>(options, &entries, &mut output)
+        },
+        (Command::Grade, PuzzleType::Towers) => {
+            grade::<towers::solver::Solver>(options, &entries, &mut output)
+        },
+        (Command::Grade, PuzzleType::Loopy) => {
+            grade::<loopy::solver::Solver>(options, &entries, &mut output)
+        },
+        (Command::Convert, _) => convert(options, &entries, &mut output),
+        (Command::Generate, _) => unreachable!(),
+    };
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|x| x == "-h" || x == "--help") {
+        print!("{}", USAGE);
+        return;
+    }
+    let options = match parse_args(&args) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::grader::Tier;
+use crate::puzzle::Puzzle;
+use crate::solver::PuzzleSolver;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::ops::RangeInclusive;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread;
+
+// Tries to remove the hints of p one at a time, in the given order, only keeping the removals that
+// leave the puzzle uniquely solvable.
+// p must be uniquely solvable to begin with.
+// The returned puzzle's difficulty is the depth its solver needed.
+pub fn remove_hints<S: PuzzleSolver>(p: &S::Puzzle, order: &[usize]) -> S::Puzzle {
+    return remove_hints_while::<S>(p, order, |_| true).0;
+}
+
+// Like remove_hints, but skips the removals that would make the puzzle harder than the hardest
+// tier in tiers, so the puzzle gets harder without overshooting.
+// Returns None if the puzzle is still easier than the easiest tier in tiers once every hint has
+// been tried.
+pub fn remove_hints_in_tiers<S: PuzzleSolver>(p: &S::Puzzle, order: &[usize], tiers: &RangeInclusive<Tier>) -> Option<S::Puzzle> {
+    let (p, tier) = remove_hints_while::<S>(p, order, |s| s.grade().tier <= *tiers.end());
+    if tiers.contains(&tier) {
+        return Some(p);
+    }
+    return None;
+}
+
+// Removes the hints in order, keeping the removals that leave the puzzle uniquely solvable and for
+// which accept returns true when given the solution's solver.
+// Also returns the tier of the resulting puzzle.
+fn remove_hints_while<S: PuzzleSolver>(p: &S::Puzzle, order: &[usize], accept: impl Fn(&S) -> bool) -> (S::Puzzle, Tier) {
+    let mut hints_to_remove: Vec<bool> = vec![false; p.number_of_hints()];
+
+    let mut s = S::new(p.clone());
+    let solutions = s.full_solve(0, false);
+    let mut difficulty = s.depth_needed();
+    let mut tier = match solutions.first() {
+        Some(x) => x.grade().tier,
+        None => Tier::Unreasonable,
+    };
+
+    for i in order.iter() {
+        hints_to_remove[*i] = true;
+        let temp_puzzle = p.with_hints_removed(&hints_to_remove, difficulty);
+        let mut s = S::new(temp_puzzle);
+        let solutions = s.full_solve(0, false);
+        if solutions.len() != 1 || !accept(&solutions[0]) {
+            // No longer uniquely solveable (or too hard), don't remove this hint.
+            hints_to_remove[*i] = false;
+        } else {
+            // How hard was it to solve
+            difficulty = s.depth_needed();
+            tier = solutions[0].grade().tier;
+        }
+    }
+
+    return (p.with_hints_removed(&hints_to_remove, difficulty), tier);
+}
+
+// What make_batch reports while it works. index is the puzzle's place in the batch.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Progress {
+    // An attempt at the puzzle was rejected for the given reason, and is retried unless it was the
+    // last one allowed.
+    Rejected { index: usize, seed: u64, reason: String },
+    // The puzzle is done, and made of the total puzzles are now done.
+    Made { index: usize, seed: u64, made: usize, total: usize },
+}
+
+// Makes count puzzles, spread over every available core.
+// Puzzle i gets the seed first_seed + i, and make is called with a generator seeded with it until it
+// returns Ok. The puzzles are returned with their seeds in order, so the result only depends on
+// first_seed and not on how the threads were scheduled.
+// If make is rejected max_attempts times in a row for a puzzle, the batch is given up and an error
+// naming that puzzle's seed is returned instead.
+// progress is called on the calling thread, in whatever order the puzzles are made in.
+pub fn make_batch<P: Send>(
+    count: usize,
+    first_seed: u64,
+    max_attempts: usize,
+    make: impl Fn(&mut StdRng) -> Result<P, String> + Sync,
+    mut progress: impl FnMut(Progress),
+) -> Result<Vec<(u64, P)>, String> {
+    let threads = match thread::available_parallelism() {
+        Ok(x) => x.get().min(count),
+        Err(_) => 1,
+    };
+    let next_index = AtomicUsize::new(0);
+    // Set once a puzzle is given up, so the other threads stop too.
+    let given_up = AtomicBool::new(false);
+    let mut results: Vec<Option<(u64, P)>> = (0..count).map(|_| None).collect();
+    let mut rejected: Vec<usize> = vec![0; count];
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel::<(usize, u64, Result<P, String>)>();
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let next_index = &next_index;
+            let given_up = &given_up;
+            let make = &make;
+            scope.spawn(move || loop {
+                let index = next_index.fetch_add(1, Ordering::SeqCst);
+                if index >= count {
+                    return;
+                }
+                let seed = first_seed.wrapping_add(index as u64);
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut done = false;
+                for _ in 0..max_attempts {
+                    if given_up.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    let result = make(&mut rng);
+                    done = result.is_ok();
+                    if sender.send((index, seed, result)).is_err() || done {
+                        break;
+                    }
+                }
+                if !done {
+                    given_up.store(true, Ordering::SeqCst);
+                    return;
+                }
+            });
+        }
+        // Otherwise the receiver would wait for this sender forever.
+        drop(sender);
+
+        let mut made = 0;
+        for (index, seed, result) in receiver {
+            match result {
+                Ok(p) => {
+                    made += 1;
+                    results[index] = Some((seed, p));
+                    progress(Progress::Made { index, seed, made, total: count });
+                },
+                Err(reason) => {
+                    rejected[index] += 1;
+                    progress(Progress::Rejected { index, seed, reason });
+                },
+            }
+        }
+    });
+
+    // The puzzles left unmade were either given up or stopped because another one was.
+    if let Some(index) = (0..count).find(|i| results[*i].is_none() && rejected[*i] == max_attempts) {
+        let seed = first_seed.wrapping_add(index as u64);
+        return Err(format!("Gave up on the puzzle with seed {} after {} rejected attempts", seed, max_attempts));
+    }
+    return Ok(results.into_iter().map(|x| x.unwrap()).collect());
+}
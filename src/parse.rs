@@ -0,0 +1,176 @@
+// Errors returned by the puzzle parsers, so that a malformed puzzle can be reported instead of
+// crashing whatever was reading it.
+
+use std::fmt;
+
+// Why a puzzle could not be read.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reason {
+    // A character that has no meaning where it was found.
+    BadCharacter(char),
+    // A number that doesn't fit in the puzzle, like a 7 in a 5 by 5 latin square.
+    ValueOutOfRange { value: u32, min: u32, max: u32 },
+    // A row that doesn't have as many cells as it should.
+    WrongRowLength { expected: usize, found: usize },
+    // A run-length encoded grid that ends before every cell is described.
+    Truncated,
+    // A run-length encoded grid that describes more cells than the grid has.
+    TooLong,
+    // A grid that should be square but isn't.
+    NotSquare { rows: usize, columns: usize },
+    // A game ID that doesn't start with the size of the puzzle.
+    MissingSize,
+    // A game ID that doesn't have one hint per row or column it should have a hint for.
+    WrongHintCount { expected: usize, found: usize },
+    // A grid wider than the solver of its family can handle.
+    TooWide { size: usize, max: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    // Where the problem is, 1-indexed, like a text editor would show it.
+    pub line: usize,
+    pub column: usize,
+    pub reason: Reason,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, reason: Reason) -> ParseError {
+        return ParseError { line, column, reason };
+    }
+
+    // The same error, but for a string that had n more characters before the part that was parsed.
+    pub fn shifted(self, n: usize) -> ParseError {
+        return ParseError { column: self.column + n, ..self };
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Reason::BadCharacter(c) => write!(f, "unexpected character '{}'", c),
+            Reason::ValueOutOfRange { value, min, max } => {
+                write!(f, "value {} is not between {} and {}", value, min, max)
+            },
+            Reason::WrongRowLength { expected, found } => {
+                write!(f, "row has {} cells instead of {}", found, expected)
+            },
+            Reason::Truncated => write!(f, "description ends before the last cell"),
+            Reason::TooLong => write!(f, "description has more cells than the grid"),
+            Reason::NotSquare { rows, columns } => {
+                write!(f, "grid has {} rows but {} columns", rows, columns)
+            },
+            Reason::MissingSize => write!(f, "missing the puzzle size"),
+            Reason::WrongHintCount { expected, found } => {
+                write!(f, "found {} hints instead of {}", found, expected)
+            },
+            Reason::TooWide { size, max } => {
+                write!(f, "grid is {} wide, at most {} is supported", size, max)
+            },
+        };
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "line {}, column {}: {}", self.line, self.column, self.reason);
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// A non-blank line of a grid, with its surrounding whitespace removed.
+pub struct Line {
+    // 1-indexed line number in the original string.
+    pub number: usize,
+    // 1-indexed column of the first character of chars in the original string.
+    pub first_column: usize,
+    pub chars: Vec<char>,
+}
+
+impl Line {
+    pub fn error(&self, index: usize, reason: Reason) -> ParseError {
+        return ParseError::new(self.number, self.first_column + index, reason);
+    }
+}
+
+pub fn grid_lines(s: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        let chars: Vec<char> = line.trim().chars().collect();
+        if chars.is_empty() {
+            continue;
+        }
+        let first_column = line.chars().take_while(|c| c.is_whitespace()).count() + 1;
+        lines.push(Line { number: i + 1, first_column, chars });
+    }
+    return lines;
+}
+
+// Reads a number made only of digits, which starts at the given column.
+pub fn number(s: &str, column: usize, min: u32, max: u32) -> Result<u32, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::new(1, column, Reason::Truncated));
+    }
+    if let Some((i, c)) = s.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+        return Err(ParseError::new(1, column + i, Reason::BadCharacter(c)));
+    }
+    let value = s.parse::<u32>().unwrap_or(u32::MAX);
+    if value < min || value > max {
+        return Err(ParseError::new(1, column, Reason::ValueOutOfRange { value, min, max }));
+    }
+    return Ok(value);
+}
+
+// Splits a game ID of the form "n:rest" into its size, at most max_size, and the rest.
+// Also returns the column at which the rest starts.
+pub fn split_size(s: &str, max_size: usize) -> Result<(usize, &str, usize), ParseError> {
+    let offset = s.chars().take_while(|c| c.is_whitespace()).count();
+    let (size, rest) = match s.trim().split_once(":") {
+        Some(x) => x,
+        None => { return Err(ParseError::new(1, offset + 1, Reason::MissingSize)); },
+    };
+    if size.is_empty() {
+        return Err(ParseError::new(1, offset + 1, Reason::MissingSize));
+    }
+    let n = number(size, offset + 1, 1, max_size as u32)?;
+    return Ok((n as usize, rest, offset + size.chars().count() + 2));
+}
+
+// Reads a single digit between min and max.
+pub fn digit(line: &Line, index: usize, min: u32, max: u32) -> Result<u8, ParseError> {
+    let c = line.chars[index];
+    let value = match c.to_digit(10) {
+        Some(x) => x,
+        None => { return Err(line.error(index, Reason::BadCharacter(c))); },
+    };
+    if value < min || value > max {
+        return Err(line.error(index, Reason::ValueOutOfRange { value, min, max }));
+    }
+    return Ok(value as u8);
+}
+
+// Checks that lines form a rectangular grid of cells and returns its width and height.
+pub fn rectangle_size(lines: &[Line]) -> Result<(usize, usize), ParseError> {
+    if lines.is_empty() {
+        return Err(ParseError::new(1, 1, Reason::Truncated));
+    }
+    let n = lines[0].chars.len();
+    for line in lines.iter() {
+        if line.chars.len() != n {
+            let index = line.chars.len().min(n);
+            return Err(line.error(index, Reason::WrongRowLength { expected: n, found: line.chars.len() }));
+        }
+    }
+    return Ok((n, lines.len()));
+}
+
+// Checks that lines form a square grid of cells and returns its size.
+pub fn square_size(lines: &[Line]) -> Result<usize, ParseError> {
+    let (n, _) = rectangle_size(lines)?;
+    if lines.len() != n {
+        let line = if lines.len() > n { lines[n].number } else { lines[lines.len() - 1].number + 1 };
+        return Err(ParseError::new(line, 1, Reason::NotSquare { rows: lines.len(), columns: n }));
+    }
+    return Ok(n);
+}
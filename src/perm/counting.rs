@@ -0,0 +1,40 @@
+// Counting permutations without listing them.
+
+use super::permutation::check_candidates;
+
+// The most positions count_compatible takes: 21! doesn't fit in a u64.
+pub const MAX_POSITIONS: usize = 20;
+
+// The number of permutations of 0..n, with n = candidates.len(), where position i takes one of the
+// values in candidates[i]. This is the permanent of the 0/1 matrix of candidates, which takes
+// O(2^n * n) time.
+// Errors if a candidate isn't one of the values 0..n, or if n is more than MAX_POSITIONS.
+pub fn count_compatible(candidates: &[Vec<u8>]) -> Result<u64, String> {
+    let n = candidates.len();
+    if n > MAX_POSITIONS {
+        return Err(format!("can't count the permutations of {} positions, at most {} fit", n, MAX_POSITIONS));
+    }
+    check_candidates(candidates)?;
+    let masks: Vec<usize> = candidates.iter().map(|c| c.iter().fold(0, |mask, v| mask | (1 << v))).collect();
+
+    // ways[taken] is the number of ways to give the first taken.count_ones() positions the values
+    // in taken.
+    let mut ways: Vec<u64> = vec![0; 1 << n];
+    ways[0] = 1;
+    for taken in 0..(1usize << n) {
+        if ways[taken] == 0 {
+            continue;
+        }
+        let position = taken.count_ones() as usize;
+        if position == n {
+            continue;
+        }
+        let mut free = masks[position] & !taken;
+        while free != 0 {
+            let value = free & free.wrapping_neg();
+            ways[taken | value] += ways[taken];
+            free &= free - 1;
+        }
+    }
+    return Ok(ways[(1 << n) - 1]);
+}
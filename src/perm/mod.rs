@@ -1 +1,3 @@
+pub mod counting;
 pub mod permutation;
+pub mod ranking;
@@ -1,9 +1,9 @@
 use std::collections::HashSet;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 
 // Note this doesn't generate the permutations uniformaly randomly
-pub fn random_perm(n: u8, restrictions: &Vec<Vec<u8>>) -> Vec<u8> {
+pub fn random_perm<R: Rng + ?Sized>(n: u8, restrictions: &Vec<Vec<u8>>, rng: &mut R) -> Vec<u8> {
     let mut positions: Vec<usize> = vec![0; n as usize];
     let mut all: HashSet<u8> = HashSet::new();
     let mut r: Vec<HashSet<usize>> = vec![HashSet::new(); n as usize];
@@ -29,8 +29,7 @@ pub fn random_perm(n: u8, restrictions: &Vec<Vec<u8>>) -> Vec<u8> {
             taken.remove(&(positions[value] as usize));
             temp_r[value].insert(positions[value] as usize);
         } else {
-            let mut rng = thread_rng();
-            let choice = *available_choices.choose(&mut rng).unwrap();
+            let choice = *available_choices.choose(rng).unwrap();
             positions[value] = choice;
             taken.insert(choice);
             value += 1 ;
@@ -44,113 +43,116 @@ pub fn random_perm(n: u8, restrictions: &Vec<Vec<u8>>) -> Vec<u8> {
     return perm;
 }
 
-// #[derive(Clone, Debug)]
-// pub struct PermIter {
-//     pub n: u8,
-//     pub positions: Vec<usize>,
-//     pub restrictions: Vec<HashSet<usize>>,
-//     pub done: bool,
-// }
-//
-// impl PermIter {
-//     pub fn new(n: u8, restrictions: &Vec<Vec<u8>>) -> PermIter {
-//         let mut r: Vec<HashSet<usize>> = vec![HashSet::new(); n as usize];
-//         for restriction in restrictions {
-//             for i in 0..n as usize {
-//                 r[restriction[i] as usize].insert(i);
-//             }
-//         }
-//
-//         let mut iter = PermIter{
-//             n,
-//             positions: vec![0; n as usize],
-//             restrictions: r,
-//             done: false
-//         };
-//         iter.find_first();
-//
-//         return iter;
-//     }
-//
-//     fn find_first(&mut self) {
-//         let n = self.n as usize;
-//
-//         let mut taken:HashSet<usize> = HashSet::new();
-//         let mut depth = 0;
-//         let mut current = 0;
-//
-//         while depth < n {
-//             if current < n && !taken.contains(&current) && !self.restrictions[depth].contains(&current){
-//                 self.positions[depth] = current;
-//                 taken.insert(current);
-//                 depth += 1;
-//                 current = 0;
-//             } else if current == n {
-//                 if depth == 0 {
-//                     self.done = true;
-//                     return;
-//                 }
-//                 depth -= 1;
-//                 current = self.positions[depth];
-//                 taken.remove(&current);
-//                 current = current + 1;
-//             } else {
-//                 current += 1;
-//             }
-//         }
-//     }
-//
-//     fn increment(&mut self) {
-//         let n = self.n as usize;
-//
-//         let mut taken:HashSet<usize> = HashSet::new();
-//         let mut depth = n - 1;
-//         let mut current = self.positions[depth] + 1;
-//
-//         for i in 0..depth {
-//             taken.insert(self.positions[i]);
-//         }
-//
-//         while depth < n {
-//             if current < n && !taken.contains(&current) && !self.restrictions[depth].contains(&current){
-//                 self.positions[depth] = current;
-//                 taken.insert(current);
-//                 depth += 1;
-//                 current = 0;
-//             } else if current == n {
-//                 if depth == 0 {
-//                     self.done = true;
-//                     return;
-//                 }
-//                 depth -= 1;
-//                 current = self.positions[depth];
-//                 taken.remove(&current);
-//                 current = current + 1;
-//             } else {
-//                 current += 1;
-//             }
-//         }
-//     }
-//
-//     fn build_perm(&self) -> Vec<u8> {
-//         let mut perm: Vec<u8> = vec![0; self.n as usize];
-//         for i in 0..self.n {
-//             perm[self.positions[i as usize]] = i;
-//         }
-//
-//         return perm;
-//     }
-// }
-//
-// impl Iterator for PermIter {
-//     type Item = Vec<u8>;
-//
-//     fn next(&mut self) -> Option<Self::Item> {
-//         if self.done {
-//             return None;
-//         }
-//         let result = self.build_perm();
-//         self.increment();
-//         return Some(result);
-//     }
-// }
+// Checks that every candidate is one of the values 0..n, with n = candidates.len().
+pub fn check_candidates(candidates: &[Vec<u8>]) -> Result<(), String> {
+    let n = candidates.len();
+    for (i, c) in candidates.iter().enumerate() {
+        if let Some(v) = c.iter().find(|v| **v as usize >= n) {
+            return Err(format!("candidate {} of position {} is not a value from 0 to {}", v, i, n - 1));
+        }
+    }
+    return Ok(());
+}
+
+// Iterates, in lexicographic order, over the permutations of 0..n where each position only takes
+// one of its allowed values.
+#[derive(Clone, Debug)]
+pub struct PermIter {
+    n: usize,
+    // allowed[i][v] is whether position i can take value v.
+    allowed: Vec<Vec<bool>>,
+    perm: Vec<u8>,
+    taken: Vec<bool>,
+    done: bool,
+}
+
+impl PermIter {
+    // The permutations that don't agree with any of the restrictions in any position, e.g. the
+    // rows that can be added to a latin square under the given rows.
+    pub fn new(n: u8, restrictions: &Vec<Vec<u8>>) -> PermIter {
+        let mut allowed: Vec<Vec<bool>> = vec![vec![true; n as usize]; n as usize];
+        for restriction in restrictions {
+            for i in 0..n as usize {
+                allowed[i][restriction[i] as usize] = false;
+            }
+        }
+        return PermIter::from_allowed(allowed);
+    }
+
+    // The permutations where position i takes one of the values in candidates[i], or an error if a
+    // candidate isn't one of the values 0..n.
+    pub fn with_candidates(candidates: &[Vec<u8>]) -> Result<PermIter, String> {
+        check_candidates(candidates)?;
+        let n = candidates.len();
+        let mut allowed: Vec<Vec<bool>> = vec![vec![false; n]; n];
+        for i in 0..n {
+            for v in &candidates[i] {
+                allowed[i][*v as usize] = true;
+            }
+        }
+        return Ok(PermIter::from_allowed(allowed));
+    }
+
+    fn from_allowed(allowed: Vec<Vec<bool>>) -> PermIter {
+        let n = allowed.len();
+        let mut iter = PermIter {
+            n,
+            allowed,
+            perm: vec![0; n],
+            taken: vec![false; n],
+            done: false,
+        };
+        iter.search(0, 0);
+        return iter;
+    }
+
+    // Moves to the first permutation, in lexicographic order, that keeps the values before
+    // position depth and has at least value at position depth.
+    fn search(&mut self, mut depth: usize, mut value: usize) {
+        let n = self.n;
+        while depth < n {
+            if value == n {
+                // Back track
+                if depth == 0 {
+                    self.done = true;
+                    return;
+                }
+                depth -= 1;
+                value = self.perm[depth] as usize;
+                self.taken[value] = false;
+                value += 1;
+            } else if !self.taken[value] && self.allowed[depth][value] {
+                self.perm[depth] = value as u8;
+                self.taken[value] = true;
+                depth += 1;
+                value = 0;
+            } else {
+                value += 1;
+            }
+        }
+    }
+
+    fn increment(&mut self) {
+        if self.n == 0 {
+            self.done = true;
+            return;
+        }
+        let depth = self.n - 1;
+        let value = self.perm[depth] as usize;
+        self.taken[value] = false;
+        self.search(depth, value + 1);
+    }
+}
+
+impl Iterator for PermIter {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.perm.clone();
+        self.increment();
+        return Some(result);
+    }
+}
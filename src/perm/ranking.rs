@@ -0,0 +1,34 @@
+// Ranking permutations of 0..n by their position in lexicographic order, and back.
+// Ranks fit in a u64 for n up to 20.
+
+fn factorial(n: usize) -> u64 {
+    return (1..=n as u64).product();
+}
+
+// The number of permutations of 0..n that come before perm in lexicographic order.
+pub fn rank(perm: &[u8]) -> u64 {
+    let n = perm.len();
+    let mut taken: Vec<bool> = vec![false; n];
+    let mut result: u64 = 0;
+    for (i, value) in perm.iter().enumerate() {
+        let value = *value as usize;
+        let smaller_left = (0..value).filter(|v| !taken[*v]).count() as u64;
+        result += smaller_left * factorial(n - 1 - i);
+        taken[value] = true;
+    }
+    return result;
+}
+
+// The permutation of 0..n with the given rank, rank must be less than n!.
+pub fn unrank(n: u8, rank: u64) -> Vec<u8> {
+    let n = n as usize;
+    let mut left: Vec<u8> = (0..n as u8).collect();
+    let mut perm: Vec<u8> = Vec::new();
+    let mut rank = rank;
+    for i in 0..n {
+        let f = factorial(n - 1 - i);
+        perm.push(left.remove((rank / f) as usize));
+        rank %= f;
+    }
+    return perm;
+}
@@ -0,0 +1,42 @@
+// What the puzzles of every puzzle family have in common.
+// This lets tools like the generator and the command line interface be written once for all
+// families.
+
+use crate::parse::ParseError;
+
+pub fn row<T>(grid: &[Vec<T>], index: usize) -> Vec<&T> {
+    return grid[index].iter().collect();
+}
+
+pub fn column<T>(grid: &[Vec<T>], index: usize) -> Vec<&T> {
+    return grid.iter().map(|x| &x[index]).collect();
+}
+
+pub trait Puzzle: Clone {
+    // What a completely filled in puzzle looks like.
+    type Solution: PartialEq;
+
+    // The number of rows (and columns) of the puzzle, or the larger of the two for rectangular
+    // puzzles.
+    fn size(&self) -> usize;
+
+    // How many nested guesses were needed to solve the puzzle when it was made.
+    fn difficulty(&self) -> u8;
+
+    // Hints are numbered from 0 to number_of_hints() - 1, in the order they appear in the
+    // puzzle's grid (and, for puzzles with hints around the grid, then in the order north, east,
+    // south, west).
+    fn number_of_hints(&self) -> usize;
+
+    // Returns a copy of this puzzle without the hints i for which hints_to_remove[i] is true.
+    fn with_hints_removed(&self, hints_to_remove: &[bool], difficulty: u8) -> Self;
+
+    // Reads a puzzle written in the format the corpus files of this family use.
+    fn from_string(s: &str) -> Result<Self, ParseError>;
+
+    // Writes the puzzle in the format the corpus files of this family use.
+    fn to_string(&self) -> String;
+
+    // Whether the given filled in puzzle respects every rule and every hint of this puzzle.
+    fn is_solution(&self, solution: &Self::Solution) -> bool;
+}
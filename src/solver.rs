@@ -0,0 +1,123 @@
+// What the solvers of every puzzle family have in common.
+// This lets tools like batch solvers, graders and generators be written once for all families.
+
+use crate::grader::Grade;
+use crate::puzzle;
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    // No solution exist
+    Unsolvable,
+    // Only one solution exists
+    UniqueSolution,
+    // Many solutions exist
+    MultipleSolutions,
+    // Don't know if solvable or not yet
+    InProgress,
+}
+
+pub trait PuzzleSolver: Clone {
+    type Puzzle: puzzle::Puzzle;
+
+    fn new(p: Self::Puzzle) -> Self;
+
+    // Solve the puzzle using all non-recursive ways we know of.
+    fn non_recursive_solve(&mut self);
+
+    // Solve the puzzle, making guesses when the non-recursive methods get stuck.
+    // Returns the solutions found. Stops looking once a second solution is found, so at most two
+    // solutions are returned.
+    fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Self>;
+
+    // Makes full_solve explore the alternatives of the guesses it makes at depths below
+    // parallel_depth on their own threads. The verdict and the depth needed are the same as
+    // without it. The search stops as soon as two solutions are found, wherever they are found.
+    fn search_in_parallel(&mut self, parallel_depth: u8);
+
+    fn status(&self) -> Status;
+
+    // The deepest level of nested guesses full_solve went into, whether the guesses there led to a
+    // solution or to a contradiction. 0 if the puzzle was solved without guessing.
+    fn depth_needed(&self) -> u8;
+
+    // How hard the techniques used so far were. Solvers returned by full_solve know every
+    // technique that led to their solution, including guesses.
+    fn grade(&self) -> Grade;
+
+    // The filled in puzzle, if the solver has found a solution.
+    fn solution(&self) -> Option<<Self::Puzzle as puzzle::Puzzle>::Solution>;
+
+    fn to_string(&self) -> String;
+}
+
+// What the copies of a solver exploring guesses on different threads share.
+// Clones share the solutions found so far, so every copy can give up as soon as the puzzle is known
+// to have more than one solution.
+#[derive(Clone)]
+pub struct Search<S> {
+    // Guesses made at depths below this are explored on their own threads.
+    pub parallel_depth: u8,
+    // The different solutions found so far, at most two.
+    found: Arc<Mutex<Vec<S>>>,
+}
+
+impl<S: PuzzleSolver> Search<S> {
+    pub fn new(parallel_depth: u8) -> Search<S> {
+        return Search { parallel_depth, found: Arc::new(Mutex::new(Vec::new())) };
+    }
+
+    pub fn should_fork(&self, depth: u8) -> bool {
+        return depth < self.parallel_depth;
+    }
+
+    // Remembers a solved solver, unless its solution was already found.
+    // The solver shouldn't have a search of its own, or it would keep this one alive forever.
+    pub fn record(&self, solved: S) {
+        let mut found = self.found.lock().unwrap();
+        if found.len() < 2 && !found.iter().any(|f| f.solution() == solved.solution()) {
+            found.push(solved);
+        }
+    }
+
+    // Whether two solutions were found, so the search can stop.
+    pub fn is_cancelled(&self) -> bool {
+        return self.found.lock().unwrap().len() >= 2;
+    }
+
+    pub fn found(&self) -> Vec<S> {
+        return self.found.lock().unwrap().clone();
+    }
+}
+
+impl<S> fmt::Debug for Search<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Search {{ parallel_depth: {} }}", self.parallel_depth);
+    }
+}
+
+// Runs full_solve on every branch at once, each on its own thread, and returns the solutions of
+// each branch in the same order as the branches.
+pub fn solve_branches<S: PuzzleSolver + Send>(branches: &mut [S], depth: u8, should_log: bool) -> Vec<Vec<S>> {
+    return thread::scope(|scope| {
+        let handles: Vec<_> = branches.iter_mut().map(|b| scope.spawn(move || b.full_solve(depth, should_log))).collect();
+        return handles.into_iter().map(|h| h.join().unwrap()).collect();
+    });
+}
+
+// The depth needed by a search that guessed something, given the depths needed when the guess and
+// its opposite were both explored one level deeper.
+// A search that explores one side after the other only explores the opposite one level deeper if
+// the guess had a solution. Otherwise the opposite is known for sure and the search carries on at
+// the same depth. This gives the same depth as that search would.
+pub fn depth_after_guess(with: u8, with_solutions: usize, without: u8) -> u8 {
+    return match with_solutions {
+        0 => with.max(without - 1),
+        1 => with.max(without),
+        _ => with,
+    };
+}
@@ -1,17 +1,21 @@
 use super::puzzle::Puzzle;
 use super::solver::Solver;
+use crate::grader::Tier;
 use crate::latin;
+use crate::latin::maker::Backend;
+use crate::maker;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
+use std::ops::RangeInclusive;
 
-pub fn make_puzzle(size: u8) -> Puzzle {
+// A random puzzle with every hint, and a random order to try removing its hints in.
+fn filled_with_removal_order<R: Rng + ?Sized>(size: u8, backend: Backend, rng: &mut R) -> (Puzzle, Vec<usize>) {
     // Start with a random latin square
-    let square: latin::puzzle::Puzzle = latin::maker::random_filled(size);
+    let square: latin::puzzle::Puzzle = latin::maker::random_filled(size, backend, rng);
 
     // Generate the full hints puzzle
     let p = Puzzle::from_latin_with_view_hints(square, 0);
 
-    let mut hints_to_remove: Vec<bool> = vec![false; p.number_of_hints()];
     let mut cell_hints: Vec<usize> = Vec::new();
     let mut view_hints: Vec<usize> = Vec::new();
 
@@ -21,26 +25,23 @@ pub fn make_puzzle(size: u8) -> Puzzle {
     for i in (size*size) as usize..(size*size + 4*size) as usize {
         view_hints.push(i);
     }
-    let mut rng = thread_rng();
     // keep view hints after cell hits to make the puzzle more towers-like instead of more latin square-like.
-    cell_hints.shuffle(&mut rng);
-    view_hints.shuffle(&mut rng);
+    cell_hints.shuffle(rng);
+    view_hints.shuffle(rng);
+    cell_hints.extend(view_hints);
 
-    let mut difficulty = 0;
+    return (p, cell_hints);
+}
 
-    for i in cell_hints.iter().chain(view_hints.iter()) {
-        hints_to_remove[*i] = true;
-        let temp_puzzle = p.with_hints_removed(&hints_to_remove, difficulty);
-        let mut s = Solver::new(temp_puzzle);
-        let solutions = s.full_solve(0, false);
-        if solutions.len() > 1 {
-            // No longer uniquely solveable, don't remove this hint.
-            hints_to_remove[*i] = false;
-        } else {
-            // How hard was it to solve
-            difficulty = s.depth_needed;
-        }
-    }
+pub fn make_puzzle<R: Rng + ?Sized>(size: u8, backend: Backend, rng: &mut R) -> Puzzle {
+    let (p, order) = filled_with_removal_order(size, backend, rng);
+    return maker::remove_hints::<Solver>(&p, &order);
+}
 
-    return p.with_hints_removed(&hints_to_remove, difficulty);
+// A puzzle whose grade is in tiers, or None if this attempt ended up too easy.
+pub fn make_puzzle_in_tiers<R: Rng + ?Sized>(
+    size: u8, backend: Backend, tiers: &RangeInclusive<Tier>, rng: &mut R,
+) -> Option<Puzzle> {
+    let (p, order) = filled_with_removal_order(size, backend, rng);
+    return maker::remove_hints_in_tiers::<Solver>(&p, &order, tiers);
 }
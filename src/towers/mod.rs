@@ -1,3 +1,5 @@
 pub mod maker;
 pub mod puzzle;
+pub mod reference;
 pub mod solver;
+pub mod verify;
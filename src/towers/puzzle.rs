@@ -1,27 +1,16 @@
 use crate::latin;
-
-// fn string_to_view(view: &str) -> Option<u8> {
-//     if view == "?" {
-//       return None;
-//     } else {
-//       return Some(view.parse::<u8>().unwrap());
-//     }
-// }
-//
-// fn string_to_cell(view: &str) -> Option<u8> {
-//     if view == "?" {
-//       return None;
-//     } else {
-//       return Some(view.parse::<u8>().unwrap() - 1);
-//     }
-// }
-
-pub fn row<T>(grid: &Vec<Vec<T>>, index: usize) -> Vec<&T> {
-    return grid[index].iter().map(|x| x).collect();
-}
-
-pub fn column<T>(grid: &Vec<Vec<T>>, index: usize) -> Vec<&T> {
-    return grid.iter().map(|x| &x[index]).collect();
+use crate::parse;
+use crate::parse::ParseError;
+use crate::parse::Reason;
+use crate::puzzle;
+use super::verify;
+
+fn char_to_hint(line: &parse::Line, index: usize, min: u32, max: u32) -> Result<Option<u8>, ParseError> {
+    if line.chars[index] == '?' {
+      return Ok(None);
+    } else {
+      return Ok(Some(parse::digit(line, index, min, max)?));
+    }
 }
 
 pub fn calculate_view_option(row: &Vec<&Option<u8>>) -> Option<u8> {
@@ -71,6 +60,7 @@ pub fn calculate_view(row: &Vec<&u8>) -> u8 {
 //     return set;
 // }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Puzzle {
     pub latin: latin::puzzle::Puzzle,
     pub north: Vec<Option<u8>>,
@@ -168,15 +158,47 @@ impl Puzzle {
         return [n.to_string() + ":", views.join("/") + ",", self.latin.to_tatham_string_without_size()].concat();
     }
 
-    pub fn clone(&self) -> Puzzle {
-        return Puzzle {
-            latin: self.latin.clone(),
-            difficulty: self.difficulty,
-            north: self.north.clone(),
-            east: self.east.clone(),
-            south: self.south.clone(),
-            west: self.west.clone(),
+    // Reads the format written by to_tatham_string: the size, then the north, south, west and east
+    // view hints separated by slashes, then optionally a comma followed by the cell hints. Tatham's
+    // Towers leaves the cell hints out when there are none, so the game ID may end after the view
+    // hints or after the comma.
+    pub fn from_tatham_string(s: &str) -> Result<Puzzle, ParseError> {
+        let (n, rest, column) = parse::split_size(s, 255)?;
+        let (views_string, grid_string) = match rest.split_once(",") {
+            Some((v, g)) => (v, Some(g)),
+            None => (rest, None),
+        };
+
+        let mut views: Vec<Option<u8>> = Vec::new();
+        let mut view_column = column;
+        for v in views_string.split("/") {
+            if v.is_empty() {
+                views.push(None);
+            } else {
+                views.push(Some(parse::number(v, view_column, 1, n as u32)? as u8));
+            }
+            view_column += v.chars().count() + 1;
         }
+        if views.len() != 4*n {
+            return Err(ParseError::new(1, column, Reason::WrongHintCount { expected: 4*n, found: views.len() }));
+        }
+
+        let latin = match grid_string {
+            Some(g) if !g.trim().is_empty() => {
+                let grid_column = column + views_string.chars().count() + 1;
+                latin::puzzle::Puzzle::from_tatham_string_with_size(n, g).map_err(|e| e.shifted(grid_column - 1))?
+            },
+            _ => latin::puzzle::Puzzle::from_grid(&vec![vec![None; n]; n]),
+        };
+
+        return Ok(Puzzle {
+            latin,
+            north: views[0..n].to_vec(),
+            south: views[n..(2*n)].to_vec(),
+            west: views[(2*n)..(3*n)].to_vec(),
+            east: views[(3*n)..(4*n)].to_vec(),
+            difficulty: 0,
+        });
     }
 
     pub fn from_latin_with_view_hints(latin: latin::puzzle::Puzzle, difficulty: u8) -> Puzzle {
@@ -202,43 +224,61 @@ impl Puzzle {
         return Puzzle { latin, north, east, south, west, difficulty };
     }
 
-    // pub fn from_string(s: &str) -> Puzzle {
-    //     let mut grid = Vec::new();
-    //
-    //     let mut iter = s.trim().split("\n").peekable();
-    //
-    //     // North hints
-    //     let first_row: &str = iter.next().unwrap().trim();
-    //     let north:Vec<Option<u8>> = first_row.trim().split(" ").map(string_to_view).collect();
-    //
-    //     // Middle rows
-    //     let mut east = Vec::new();
-    //     let mut west = Vec::new();
-    //     let mut row: &str = iter.next().unwrap().trim();
-    //     while iter.peek().is_some() {
-    //       let mut grid_row = Vec::new();
-    //       let mut row_iter = row.split(" ").peekable();
-    //       west.push(string_to_view(row_iter.next().unwrap()));
-    //       let mut value = row_iter.next().unwrap();
-    //       while row_iter.peek().is_some() {
-    //           grid_row.push(string_to_cell(value));
-    //           value = row_iter.next().unwrap();
-    //       }
-    //       east.push(string_to_view(value));
-    //       grid.push(grid_row);
-    //       row = iter.next().unwrap().trim();
-    //     }
-    //
-    //     // South hints
-    //     // Since iter.peek() is none, row is the last row.
-    //     let south:Vec<Option<u8>> = row.trim().split(" ").map(string_to_view).collect();
-    //
-    //     return Puzzle {
-    //         size: north.len(), north, east, south, west, grid,
-    //     };
-    // }
-
-    pub fn number_of_hints(&self) -> usize {
+    // Reads the format written by to_human_string.
+    // Note that, just like in to_human_string, cell values are 0-indexed.
+    pub fn from_human_string(s: &str) -> Result<Puzzle, ParseError> {
+        let lines = parse::grid_lines(s);
+        if lines.is_empty() {
+            return Err(ParseError::new(1, 1, Reason::Truncated));
+        }
+        let n = lines[0].chars.len();
+        if lines.len() != n + 2 {
+            let line = if lines.len() > n + 2 { lines[n + 2].number } else { lines[lines.len() - 1].number + 1 };
+            return Err(ParseError::new(line, 1, Reason::NotSquare { rows: lines.len().saturating_sub(2), columns: n }));
+        }
+        for (k, line) in lines.iter().enumerate() {
+            let expected = if k == 0 || k == n + 1 { n } else { n + 2 };
+            if line.chars.len() != expected {
+                let index = line.chars.len().min(expected);
+                return Err(line.error(index, Reason::WrongRowLength { expected, found: line.chars.len() }));
+            }
+        }
+
+        let views = |line: &parse::Line| (0..n).map(|j| char_to_hint(line, j, 1, n as u32)).collect::<Result<Vec<Option<u8>>, ParseError>>();
+        let north = views(&lines[0])?;
+        let south = views(&lines[n + 1])?;
+        let mut east = Vec::new();
+        let mut west = Vec::new();
+        let mut grid = Vec::new();
+        for line in lines.iter().skip(1).take(n) {
+            west.push(char_to_hint(line, 0, 1, n as u32)?);
+            let mut grid_row = Vec::new();
+            for j in 1..(n + 1) {
+                grid_row.push(char_to_hint(line, j, 0, n as u32 - 1)?);
+            }
+            grid.push(grid_row);
+            east.push(char_to_hint(line, n + 1, 1, n as u32)?);
+        }
+
+        return Ok(Puzzle {
+            latin: latin::puzzle::Puzzle::from_grid(&grid), north, east, south, west, difficulty: 0,
+        });
+    }
+}
+
+impl puzzle::Puzzle for Puzzle {
+    // The solution's cells are 0-indexed, like the values in the puzzle's grid.
+    type Solution = Vec<Vec<u8>>;
+
+    fn size(&self) -> usize {
+        return self.latin.size;
+    }
+
+    fn difficulty(&self) -> u8 {
+        return self.difficulty;
+    }
+
+    fn number_of_hints(&self) -> usize {
         let mut total = 0;
 
         for column in &self.latin.grid {
@@ -273,7 +313,7 @@ impl Puzzle {
         return total;
     }
 
-    pub fn with_hints_removed(&self, hints_to_remove: &Vec<bool>, difficulty: u8) -> Puzzle {
+    fn with_hints_removed(&self, hints_to_remove: &[bool], difficulty: u8) -> Puzzle {
         let n = self.latin.grid.len();
         let mut total = 0;
 
@@ -328,4 +368,21 @@ impl Puzzle {
 
         return Puzzle { latin, north, east, south, west, difficulty };
     }
+
+    // Towers puzzles can either be given as a Tatham game ID or as a grid.
+    fn from_string(s: &str) -> Result<Puzzle, ParseError> {
+        if s.contains(':') {
+            return Puzzle::from_tatham_string(s);
+        } else {
+            return Puzzle::from_human_string(s);
+        }
+    }
+
+    fn to_string(&self) -> String {
+        return self.to_human_string();
+    }
+
+    fn is_solution(&self, solution: &Vec<Vec<u8>>) -> bool {
+        return verify::verify(self, solution).is_ok();
+    }
 }
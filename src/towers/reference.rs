@@ -0,0 +1,55 @@
+// A plain backtracking solver to check the real one against. It fills the grid a row at a time,
+// trying every permutation that keeps the given cells and the columns latin, and checks the views of
+// each row once it's placed and of each column once the grid is full.
+
+use crate::perm::permutation::PermIter;
+use crate::puzzle::column;
+use crate::puzzle::row;
+use super::puzzle::calculate_view;
+use super::puzzle::Puzzle;
+
+// The solutions of the puzzle, stopping once limit of them are found.
+pub fn solutions(puzzle: &Puzzle, limit: usize) -> Vec<Vec<Vec<u8>>> {
+    let mut grid = Vec::new();
+    let mut found = Vec::new();
+    fill(puzzle, &mut grid, limit, &mut found);
+    return found;
+}
+
+// Whether the line, seen from its start and from its end, shows as many towers as the hints say.
+fn respects_views(line: &mut Vec<&u8>, from_start: Option<u8>, from_end: Option<u8>) -> bool {
+    if from_start.is_some() && from_start != Some(calculate_view(line)) {
+        return false;
+    }
+    line.reverse();
+    return from_end.is_none() || from_end == Some(calculate_view(line));
+}
+
+fn fill(puzzle: &Puzzle, grid: &mut Vec<Vec<u8>>, limit: usize, found: &mut Vec<Vec<Vec<u8>>>) {
+    let n = puzzle.latin.size;
+    if found.len() >= limit {
+        return;
+    }
+    let i = grid.len();
+    if i == n {
+        if (0..n).all(|j| respects_views(&mut column(grid, j), puzzle.north[j], puzzle.south[j])) {
+            found.push(grid.clone());
+        }
+        return;
+    }
+    // The given value of each cell of the row, or every value not yet used in its column.
+    let candidates: Vec<Vec<u8>> = (0..n).map(|j| match puzzle.latin.grid[i][j] {
+        Some(hint) => vec![hint],
+        None => (0..n as u8).filter(|v| grid.iter().all(|r| r[j] != *v)).collect(),
+    }).collect();
+    for r in PermIter::with_candidates(&candidates).unwrap() {
+        if grid.iter().any(|above| above.iter().zip(r.iter()).any(|(a, b)| a == b)) {
+            continue;
+        }
+        grid.push(r);
+        if respects_views(&mut row(grid, i), puzzle.west[i], puzzle.east[i]) {
+            fill(puzzle, grid, limit, found);
+        }
+        grid.pop();
+    }
+}
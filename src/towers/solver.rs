@@ -1,13 +1,20 @@
 use super::puzzle::Puzzle;
-use super::puzzle::row;
-use super::puzzle::column;
-use super::puzzle::calculate_view;
+use crate::puzzle::row;
+use crate::puzzle::column;
+use crate::latin::solver::solution_from_candidates;
+use super::verify::verify;
+use crate::grader;
+use crate::grader::Grade;
+use crate::solver::PuzzleSolver;
+use crate::solver::Search;
+pub use crate::solver::Status;
 use std::collections::HashSet;
+pub mod step;
 mod row_solver;
 mod latin_solver;
 mod depth_solver;
 
-use std::time::Instant;
+use step::*;
 
 // (row, column)
 #[derive(Clone)]
@@ -15,26 +22,14 @@ use std::time::Instant;
 #[derive(Copy)]
 pub struct Coordinate (usize, usize);
 
-#[derive(Debug)]
-enum Direction {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
     NORTH,
     EAST,
     SOUTH,
     WEST,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Status {
-    // No solution exists
-    Unsolvable,
-    // No solution exists
-    UniqueSolution,
-    // No solution exists
-    MultipleSolutions,
-    // Don't know if solvable or not yet
-    InProgress,
-}
-
 fn view_to_string(view: &Option<u8>) -> String {
     match view {
         Some(x) => x.to_string(),
@@ -54,6 +49,7 @@ fn possibilities_to_detailed_string(p: &HashSet<u8>, size: usize) -> String {
     return result;
 }
 
+#[derive(Clone)]
 pub struct Solver {
     puzzle: Puzzle,
     grid: Vec<Vec<HashSet<u8>>>,
@@ -66,6 +62,10 @@ pub struct Solver {
     change_flag: bool,
     status: Status,
     pub depth_needed: u8,
+    // Every value removed so far, grouped by the technique that removed it.
+    pub log: Vec<Step>,
+    // Shared with the copies exploring guesses on other threads, if any.
+    pub search: Option<Search<Solver>>,
 }
 
 fn get_vec<'a>(grid: &'a Vec<Vec<HashSet<u8>>>, d: &Direction, i: usize) -> Vec<&'a HashSet<u8>> {
@@ -84,7 +84,7 @@ fn get_vec<'a>(grid: &'a Vec<Vec<HashSet<u8>>>, d: &Direction, i: usize) -> Vec<
 
 // i1 = the row/column chosen
 // i2 = how far in the row/column
-fn get_coordinate<'a>(d: &Direction, n: usize, i1: usize, i2: usize) -> Coordinate {
+fn get_coordinate(d: &Direction, n: usize, i1: usize, i2: usize) -> Coordinate {
     let mut c = Coordinate(i1, i2);
     // If we're starting from the end of the row columns, then we need to do n - that index instead.
     match d {
@@ -139,6 +139,24 @@ impl Solver {
       return rows.join("\n");
     }
 
+    // The values each cell can still take, in increasing order.
+    // Like in the puzzle's grid, values are 0-indexed.
+    pub fn candidates(&self) -> Vec<Vec<Vec<u8>>> {
+        return self.grid.iter().map(|row| row.iter().map(|p| {
+            let mut values: Vec<u8> = p.iter().cloned().collect();
+            values.sort();
+            values
+        }).collect()).collect();
+    }
+
+    // The filled in grid, if every cell is down to a single value.
+    pub fn solution(&self) -> Option<Vec<Vec<u8>>> {
+        if self.status == Status::Unsolvable {
+            return None;
+        }
+        return solution_from_candidates(&self.candidates());
+    }
+
     pub fn new(p: Puzzle) -> Solver {
         let n = p.latin.size;
         let mut grid: Vec<Vec<HashSet<u8>>> = Vec::new();
@@ -190,7 +208,7 @@ impl Solver {
             }
         }
 
-        return Solver {
+        let mut s = Solver {
             puzzle: p,
             grid,
             solved_count: recently_solved.len(),
@@ -202,30 +220,33 @@ impl Solver {
             change_flag: false,
             status: Status::InProgress,
             depth_needed: 0,
+            log: Vec::new(),
+            search: None,
+        };
+        // The solution is only checked when the last cell is solved by removing values, which
+        // never happens if every cell is given.
+        if s.solved_count == n * n {
+            s.status = if s.satisfies_contraints() { Status::UniqueSolution } else { Status::Unsolvable };
         }
+        return s;
     }
 
-    fn clone (&self) -> Solver {
-        return Solver {
-            puzzle: self.puzzle.clone(),
-            grid: self.grid.clone(),
-            solved_count: self.solved_count,
-            recently_solved: self.recently_solved.clone(),
-            value_count_by_row: self.value_count_by_row.clone(),
-            value_count_by_column: self.value_count_by_column.clone(),
-            recently_unique_in_row: self.recently_unique_in_row.clone(),
-            recently_unique_in_column: self.recently_unique_in_column.clone(),
-            change_flag: self.change_flag,
-            status: self.status,
-            depth_needed: self.depth_needed,
-        }
-    }
-
-    fn remove(& mut self, c: &Coordinate, value: &u8) {
+    fn remove(& mut self, c: &Coordinate, value: &u8, technique: Technique, clue: Clue) {
         let n = self.puzzle.latin.size;
         let set = self.grid[c.0].get_mut(c.1).unwrap();
         let has_removed = set.remove(value);
         if has_removed {
+            let solved = if set.len() == 1 { set.iter().next().cloned() } else { None };
+            match self.log.last_mut() {
+                Some(s) if s.technique == technique && s.clue == clue && s.row == c.0 && s.column == c.1 => {
+                    s.removed.push(*value);
+                    s.solved = solved;
+                },
+                _ => {
+                    self.log.push(Step { technique, clue, row: c.0, column: c.1, removed: vec![*value], solved });
+                },
+            }
+            let set = self.grid[c.0].get_mut(c.1).unwrap();
             self.value_count_by_row[c.0][*value as usize] -= 1;
             self.value_count_by_column[c.1][*value as usize] -= 1;
             if set.len() == 1 {
@@ -252,66 +273,22 @@ impl Solver {
         }
     }
 
-    fn set(& mut self, c: &Coordinate, value: &u8) -> bool {
+    fn set(& mut self, c: &Coordinate, value: &u8, technique: Technique, clue: Clue) -> bool {
         for i in 0..self.puzzle.latin.size {
             let u = i as u8;
             if u != *value {
-                self.remove(c, &u);
+                self.remove(c, &u, technique, clue);
             }
         }
         return true;
     }
 
+    // Whether every cell has a single value left and together they solve the puzzle.
     fn satisfies_contraints(&self) -> bool {
-        let n = self.puzzle.latin.size;
-        // Check if each element in each row is unique.
-        for row in 0..n {
-            let mut seen: HashSet<u8> = HashSet::new();
-            for column in 0..n {
-                if self.grid[row][column].len() != 1 {
-                    return false;
-                } else {
-                    seen.insert(*self.grid[row][column].iter().next().unwrap());
-                }
-            }
-            if seen.len() != n {
-                return false;
-            }
-        }
-        // Check if each element in each column is unique.
-        for column in 0..n {
-            let mut seen: HashSet<u8> = HashSet::new();
-            for row in 0..n {
-                if self.grid[row][column].len() != 1 {
-                    return false;
-                } else {
-                    seen.insert(*self.grid[row][column].iter().next().unwrap());
-                }
-            }
-            if seen.len() != n {
-                return false;
-            }
-        }
-        // Check if each view is respected
-        for d in [Direction::NORTH, Direction::EAST, Direction::SOUTH, Direction::WEST] {
-            let views: &Vec<Option<u8>> = match d {
-                Direction::NORTH => &self.puzzle.north,
-                Direction::EAST => &self.puzzle.east,
-                Direction::SOUTH => &self.puzzle.south,
-                Direction::WEST => &self.puzzle.west,
-            };
-            for index in 0..n {
-                if views[index].is_none() {
-                    continue;
-                }
-                let view = views[index].unwrap();
-                let values: Vec<&u8> = get_vec(&self.grid, &d, index).iter().map(|x| x.iter().next().unwrap()).collect();
-                if calculate_view(&values) != view {
-                    return false;
-                }
-            }
-        }
-        return true;
+        return match solution_from_candidates(&self.candidates()) {
+            Some(grid) => verify(&self.puzzle, &grid).is_ok(),
+            None => false,
+        };
     }
 
     fn view_solve(& mut self) {
@@ -368,9 +345,10 @@ impl Solver {
 
         let (still_potentially_solvable, to_remove) = row_solver::solve(view.unwrap(), &get_vec(&self.grid, &from, index));
 
+        let clue = Clue::View { from, index, view: view.unwrap() };
         for i in to_remove {
             let c = get_coordinate(&from, n, index, i.0);
-            self.remove(&c, &i.1);
+            self.remove(&c, &i.1, Technique::ViewAnalysis, clue);
         }
 
         return still_potentially_solvable;
@@ -427,6 +405,46 @@ impl Solver {
         }
     }
 
+    // The easiest step that can be taken next, or None if the solver can't make any progress
+    // without guessing, or if there's nothing left to find because the puzzle is already filled in
+    // or can't be solved.
+    // To get hints for a partly filled in puzzle, make a solver for the puzzle with the player's
+    // values in its grid.
+    pub fn next_hint(&self) -> Option<Step> {
+        if self.puzzle.latin.grid.iter().flatten().all(|x| x.is_some()) {
+            return None;
+        }
+        let mut copy = self.clone();
+        let start = copy.log.len();
+        copy.non_recursive_solve();
+        if copy.status == Status::Unsolvable {
+            return None;
+        }
+        let steps: Vec<Step> = copy.log.drain(start..).collect();
+        // Steps that only use a view hint can always be taken, so give the easiest of them first.
+        let from_hints = steps.iter().filter(|s| s.technique <= Technique::ViewBound).min_by_key(|s| s.technique);
+        if let Some(s) = from_hints {
+            return Some(s.clone());
+        }
+        // Removing the value of a solved cell from its row and column is obvious to the player, so
+        // skip to the first step that does something else.
+        return match steps.iter().find(|s| s.technique != Technique::SolvedCell) {
+            Some(s) => Some(s.clone()),
+            None => steps.first().cloned(),
+        };
+    }
+
+    // Tells the other copies searching in parallel about the solutions found.
+    fn record_solutions(&self, solutions: &[Solver]) {
+        if let Some(search) = &self.search {
+            for s in solutions.iter().filter(|s| s.status == Status::UniqueSolution) {
+                let mut s = s.clone();
+                s.search = None;
+                search.record(s);
+            }
+        }
+    }
+
     pub fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Solver> {
         self.depth_needed = depth;
         // let start = Instant::now();
@@ -440,9 +458,12 @@ impl Solver {
                 solutions = self.depth_solve(depth, should_log);
             } else {
                 solutions = Vec::new();
-                solutions.push(self.clone());
+                if self.status == Status::UniqueSolution {
+                    solutions.push(self.clone());
+                }
             }
         }
+        self.record_solutions(&solutions);
 
         // let duration = start.elapsed();
         // let indent = " ".repeat((8 * depth) as usize);
@@ -468,12 +489,13 @@ impl Solver {
             }
         }
         for (column, view) in north_hints {
+            let clue = Clue::View { from: Direction::NORTH, index: column, view };
             if view == 1 {
-                self.set(&Coordinate(0, column), &((n-1) as u8));
+                self.set(&Coordinate(0, column), &((n-1) as u8), Technique::ViewOfOne, clue);
             } else {
                 for row in 0..n {
                     for value in (1 + ((n + row) as u8) - view)..(n as u8) {
-                        self.remove(&Coordinate(row, column), &value);
+                        self.remove(&Coordinate(row, column), &value, Technique::ViewBound, clue);
                     }
                 }
             }
@@ -489,13 +511,14 @@ impl Solver {
             }
         }
         for (row, view) in east_hints {
+            let clue = Clue::View { from: Direction::EAST, index: row, view };
             if view == 1 {
-                self.set(&Coordinate(row, n - 1), &((n-1) as u8));
+                self.set(&Coordinate(row, n - 1), &((n-1) as u8), Technique::ViewOfOne, clue);
             } else {
                 for i in 0..n {
                     let column = n - i - 1;
                     for value in (1 + ((n + i) as u8) - view)..(n as u8) {
-                        self.remove(&Coordinate(row, column), &value);
+                        self.remove(&Coordinate(row, column), &value, Technique::ViewBound, clue);
                     }
                 }
             }
@@ -511,13 +534,14 @@ impl Solver {
             }
         }
         for (column, view) in south_hints {
+            let clue = Clue::View { from: Direction::SOUTH, index: column, view };
             if view == 1 {
-                self.set(&Coordinate(n - 1, column), &((n-1) as u8));
+                self.set(&Coordinate(n - 1, column), &((n-1) as u8), Technique::ViewOfOne, clue);
             } else if view != 0 {
                 for i in 0..n {
                     let row = n - i - 1;
                     for value in (1 + ((n + i) as u8) - view)..(n as u8) {
-                        self.remove(&Coordinate(row, column), &value);
+                        self.remove(&Coordinate(row, column), &value, Technique::ViewBound, clue);
                     }
                 }
             }
@@ -533,15 +557,56 @@ impl Solver {
             }
         }
         for (row, view) in west_hints {
+            let clue = Clue::View { from: Direction::WEST, index: row, view };
             if view == 1 {
-                self.set(&Coordinate(row, 0), &((n-1) as u8));
+                self.set(&Coordinate(row, 0), &((n-1) as u8), Technique::ViewOfOne, clue);
             } else {
                 for column in 0..n {
                     for value in (1 + ((n + column) as u8) - view)..(n as u8) {
-                        self.remove(&Coordinate(row, column), &value);
+                        self.remove(&Coordinate(row, column), &value, Technique::ViewBound, clue);
                     }
                 }
             }
         }
     }
 }
+
+impl PuzzleSolver for Solver {
+    type Puzzle = Puzzle;
+
+    fn new(p: Puzzle) -> Solver {
+        return Solver::new(p);
+    }
+
+    fn non_recursive_solve(&mut self) {
+        Solver::non_recursive_solve(self);
+    }
+
+    fn full_solve(&mut self, depth: u8, should_log: bool) -> Vec<Solver> {
+        return Solver::full_solve(self, depth, should_log);
+    }
+
+    fn search_in_parallel(&mut self, parallel_depth: u8) {
+        self.search = Some(Search::new(parallel_depth));
+    }
+
+    fn status(&self) -> Status {
+        return self.status;
+    }
+
+    fn depth_needed(&self) -> u8 {
+        return self.depth_needed;
+    }
+
+    fn grade(&self) -> Grade {
+        return grader::grade(self.log.iter().map(|s| s.technique));
+    }
+
+    fn solution(&self) -> Option<Vec<Vec<u8>>> {
+        return Solver::solution(self);
+    }
+
+    fn to_string(&self) -> String {
+        return self.to_detailed_string();
+    }
+}
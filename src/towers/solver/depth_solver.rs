@@ -2,6 +2,8 @@ use std::collections::HashSet;
 use super::Coordinate;
 use super::Solver;
 use super::Status;
+use super::step::*;
+use crate::solver;
 
 // (row, column, value)
 #[derive(Clone, Debug, Copy, Hash, PartialEq, Eq)]
@@ -15,6 +17,16 @@ impl Solver {
         let mut can_skip: HashSet<Triple> = HashSet::new();
         let mut solutions: Vec<Solver> = Vec::new();
 
+        let mut forking = false;
+        if let Some(search) = &self.search {
+            if search.is_cancelled() {
+                // Another copy found a second solution.
+                self.status = Status::MultipleSolutions;
+                return search.found();
+            }
+            forking = search.should_fork(depth);
+        }
+
         for i in 0..n {
             for j in 0..n {
                 let num = self.grid[i][j].len();
@@ -22,13 +34,33 @@ impl Solver {
                     continue;
                 }
                 let mut found_solution = false;
-                for v in self.grid[i][j].iter() {
-                    if can_skip.contains(&Triple(i, j, *v)) {
-                        continue;
+                // Guess the values in order, so the depth needed doesn't depend on how the set
+                // happens to be laid out.
+                let mut values: Vec<u8> = self.grid[i][j].iter().cloned().filter(|v| !can_skip.contains(&Triple(i, j, *v))).collect();
+                values.sort();
+                // When forking, every value is explored at once, each on its own thread. The results
+                // are then gone through in order like below, so the values after the first
+                // contradiction don't count, and the depth needed is the same as without forking.
+                let mut forked: Vec<Option<(Solver, Vec<Solver>)>> = values.iter().map(|_| None).collect();
+                if forking {
+                    let mut copies: Vec<Solver> = values.iter().map(|v| self.with_guess(i, j, v)).collect();
+                    let results = solver::solve_branches(&mut copies, depth + 1, should_log);
+                    let search = self.search.as_ref().unwrap();
+                    if search.is_cancelled() {
+                        self.status = Status::MultipleSolutions;
+                        return search.found();
                     }
-                    let mut copy = self.clone();
-                    copy.set(&Coordinate(i, j), v);
-                    let mut copy_solutions = copy.full_solve(depth + 1, should_log);
+                    forked = copies.into_iter().zip(results).map(Some).collect();
+                }
+                for (k, v) in values.iter().enumerate() {
+                    let (copy, mut copy_solutions) = match forked[k].take() {
+                        Some(x) => x,
+                        None => {
+                            let mut copy = self.with_guess(i, j, v);
+                            let copy_solutions = copy.full_solve(depth + 1, should_log);
+                            (copy, copy_solutions)
+                        },
+                    };
                     if copy.depth_needed > self.depth_needed {
                         self.depth_needed = copy.depth_needed;
                     }
@@ -66,7 +98,7 @@ impl Solver {
         match to_remove {
             Some(x) => {
                 // We found a contradiction, so remove that possibility.
-                self.remove(&Coordinate(x.0, x.1), &x.2);
+                self.remove(&Coordinate(x.0, x.1), &x.2, Technique::TrialAndError, Clue::Nothing);
             },
             None => {
                 // No contradictions found, so every possible choice from here can lead to a
@@ -77,4 +109,11 @@ impl Solver {
 
         return solutions;
     }
+
+    // A copy of the solver with the value v guessed for the cell (i, j).
+    fn with_guess(&self, i: usize, j: usize, v: &u8) -> Solver {
+        let mut copy = self.clone();
+        copy.set(&Coordinate(i, j), v, Technique::Guess, Clue::Nothing);
+        return copy;
+    }
 }
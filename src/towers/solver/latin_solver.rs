@@ -1,6 +1,7 @@
 use super::Coordinate;
 use super::Solver;
 use super::Status;
+use super::step::*;
 mod graph_solver;
 
 // Returns how many times it had to backtrack
@@ -35,12 +36,13 @@ impl Solver {
         while !self.recently_solved.is_empty() && self.status == Status::InProgress {
             let c = self.recently_solved.pop().unwrap();
             let value = self.grid[c.0][c.1].iter().next().unwrap().clone();
+            let clue = Clue::Cell { row: c.0, column: c.1 };
             for i in 0..self.puzzle.latin.size {
                 if i != c.1 {
-                    self.remove(&Coordinate(c.0, i), &value);
+                    self.remove(&Coordinate(c.0, i), &value, Technique::SolvedCell, clue);
                 }
                 if i != c.0 {
-                    self.remove(&Coordinate(i, c.1), &value);
+                    self.remove(&Coordinate(i, c.1), &value, Technique::SolvedCell, clue);
                 }
             }
         }
@@ -51,7 +53,7 @@ impl Solver {
             let (row, value) = self.recently_unique_in_row.pop().unwrap();
             for i in 0..self.puzzle.latin.size {
                 if self.grid[row][i].contains(&value) {
-                    self.set(&Coordinate(row, i), &value);
+                    self.set(&Coordinate(row, i), &value, Technique::UniqueInRow, Clue::Row(row));
                 }
             }
         }
@@ -62,7 +64,7 @@ impl Solver {
             let (column, value) = self.recently_unique_in_column.pop().unwrap();
             for i in 0..self.puzzle.latin.size {
                 if self.grid[i][column].contains(&value) {
-                    self.set(&Coordinate(i, column), &value);
+                    self.set(&Coordinate(i, column), &value, Technique::UniqueInColumn, Clue::Column(column));
                 }
             }
         }
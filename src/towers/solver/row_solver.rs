@@ -253,6 +253,10 @@ pub fn solve(view: u8, row: &Vec<&HashSet<u8>>) -> (bool, Vec<(usize, u8)>) {
     let possibly_seen_values: Vec<u8> = (0..(n as u8)).filter(|x| !values_not_seen_for_sure.iter().any(|y| x==y)).collect();
     let possibly_seen_positions = (0..n).filter(|x| !positions_not_seen_for_sure.iter().any(|y| x==y));
     let v = possibly_seen_values.len();
+    if v < view as usize {
+        // Not enough values can be seen.
+        return (false, Vec::new());
+    }
     for (k, pos) in possibly_seen_positions.enumerate() {
         for value in possibly_seen_values.iter().skip(1 + k + v - (view as usize)) {
             to_remove.push((pos, *value));
@@ -0,0 +1,130 @@
+use super::Direction;
+use crate::grader;
+use crate::grader::Tier;
+
+// The techniques the solver uses to remove values from cells, from easiest to hardest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    // A view of 1 means the tallest tower is right next to the hint.
+    ViewOfOne,
+    // A view of v means the k-th cell from the hint (starting at 0) is at most n - v + k tall,
+    // otherwise there wouldn't be enough room for v towers to be seen.
+    ViewBound,
+    // A solved cell's value can't appear again in its row or column.
+    SolvedCell,
+    // The only cell of a row that can take a value has to take it.
+    UniqueInRow,
+    // The only cell of a column that can take a value has to take it.
+    UniqueInColumn,
+    // No arrangement of the row or column that respects its view hint uses the value there.
+    ViewAnalysis,
+    // Guessing the value leads to a puzzle without solutions.
+    TrialAndError,
+    // A value tried by the depth solver to see where it leads.
+    Guess,
+}
+
+impl grader::Technique for Technique {
+    fn tier(&self) -> Tier {
+        return match self {
+            Technique::ViewOfOne | Technique::ViewBound | Technique::SolvedCell => Tier::Easy,
+            Technique::UniqueInRow | Technique::UniqueInColumn => Tier::Easy,
+            Technique::ViewAnalysis => Tier::Hard,
+            Technique::TrialAndError | Technique::Guess => Tier::Unreasonable,
+        };
+    }
+}
+
+// What a step was based on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clue {
+    // Nothing in particular, for guesses.
+    Nothing,
+    // A solved cell.
+    Cell { row: usize, column: usize },
+    Row(usize),
+    Column(usize),
+    // The view hint seen from the given side of the row or column index.
+    View { from: Direction, index: usize, view: u8 },
+}
+
+// Values removed from one cell by one use of a technique.
+// Rows, columns and values are 0-indexed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub technique: Technique,
+    pub clue: Clue,
+    pub row: usize,
+    pub column: usize,
+    pub removed: Vec<u8>,
+    // The value the cell was left with, if the step solved it.
+    pub solved: Option<u8>,
+}
+
+fn values_to_string(values: &[u8]) -> String {
+    let values: Vec<String> = values.iter().map(|v| (v + 1).to_string()).collect();
+    return match values.len() {
+        1 => values[0].clone(),
+        _ => format!("{} or {}", values[..values.len() - 1].join(", "), values[values.len() - 1]),
+    };
+}
+
+fn describe_view(from: &Direction, index: usize, view: u8) -> String {
+    let line = match from {
+        Direction::NORTH | Direction::SOUTH => format!("column {}", index + 1),
+        Direction::EAST | Direction::WEST => format!("row {}", index + 1),
+    };
+    let side = match from {
+        Direction::NORTH => "north",
+        Direction::EAST => "east",
+        Direction::SOUTH => "south",
+        Direction::WEST => "west",
+    };
+    return format!("the view of {} from the {} of {}", view, side, line);
+}
+
+impl Step {
+    // A sentence explaining the step to a player.
+    // Rows, columns and values are 1-indexed, like the player sees them.
+    pub fn explanation(&self) -> String {
+        let cell = format!("Cell ({}, {})", self.row + 1, self.column + 1);
+        if let (Technique::Guess, Some(v)) = (self.technique, self.solved) {
+            return format!("Guess that {} is {}.", cell.to_lowercase(), v + 1);
+        }
+        let conclusion = match (self.technique, self.solved) {
+            (Technique::ViewOfOne | Technique::UniqueInRow | Technique::UniqueInColumn, Some(v)) => {
+                format!("{} must be {}", cell, v + 1)
+            },
+            _ => format!("{} can't be {}", cell, values_to_string(&self.removed)),
+        };
+        let reason = match (self.technique, self.clue) {
+            (Technique::ViewOfOne, Clue::View { from, index, view }) => {
+                format!("with {}, the tallest tower has to be right next to the hint", describe_view(&from, index, view))
+            },
+            (Technique::ViewBound, Clue::View { from, index, view }) => {
+                format!("with {}, a taller tower there would hide too many others", describe_view(&from, index, view))
+            },
+            (Technique::SolvedCell, Clue::Cell { row, column }) => {
+                let value = self.removed[0] + 1;
+                format!("cell ({}, {}) is already {}", row + 1, column + 1, value)
+            },
+            (Technique::UniqueInRow, Clue::Row(row)) => {
+                format!("it is the only cell of row {} that can be {}", row + 1, self.solved.unwrap_or(0) + 1)
+            },
+            (Technique::UniqueInColumn, Clue::Column(column)) => {
+                format!("it is the only cell of column {} that can be {}", column + 1, self.solved.unwrap_or(0) + 1)
+            },
+            (Technique::ViewAnalysis, Clue::View { from, index, view }) => {
+                format!("no arrangement that respects {} allows it", describe_view(&from, index, view))
+            },
+            (Technique::TrialAndError, _) => String::from("trying it leads to a puzzle without solutions"),
+            _ => String::from("it was guessed"),
+        };
+        return match (self.technique, self.solved) {
+            (Technique::ViewOfOne | Technique::UniqueInRow | Technique::UniqueInColumn, _) | (_, None) => {
+                format!("{}: {}.", conclusion, reason)
+            },
+            (_, Some(v)) => format!("{}: {}. That leaves only {}.", conclusion, reason, v + 1),
+        };
+    }
+}
@@ -0,0 +1,72 @@
+// Checking a filled in towers grid against a puzzle without the solver, and saying which rule it
+// breaks.
+
+use crate::latin;
+use crate::puzzle::column;
+use crate::puzzle::row;
+use super::puzzle::calculate_view;
+use super::puzzle::Puzzle;
+use super::solver::Direction;
+
+use std::fmt;
+
+// The first rule a candidate solution was found to break.
+// Rows and columns are 0-indexed like in the puzzle's grid, but displayed 1-indexed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    // The grid isn't a latin square, or doesn't keep a given value.
+    Latin(latin::verify::Violation),
+    // Looking into the grid from the side, at the given row (east and west) or column (north and
+    // south), doesn't show as many towers as the hint says.
+    View { side: Direction, index: usize, hint: u8, seen: u8 },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Violation::Latin(v) => write!(f, "{}", v),
+            Violation::View { side, index, hint, seen } => {
+                let (side, line) = match side {
+                    Direction::NORTH => ("north", "column"),
+                    Direction::EAST => ("east", "row"),
+                    Direction::SOUTH => ("south", "column"),
+                    Direction::WEST => ("west", "row"),
+                };
+                write!(f, "{} towers are seen from the {} of {} {} instead of {}", seen, side, line, index + 1, hint)
+            },
+        };
+    }
+}
+
+// Checks that the candidate solves the latin part of the puzzle and that every view hint sees as
+// many towers as it says.
+pub fn verify(puzzle: &Puzzle, candidate: &[Vec<u8>]) -> Result<(), Violation> {
+    latin::verify::verify(&puzzle.latin, candidate).map_err(Violation::Latin)?;
+    for side in [Direction::NORTH, Direction::EAST, Direction::SOUTH, Direction::WEST] {
+        let hints = match side {
+            Direction::NORTH => &puzzle.north,
+            Direction::EAST => &puzzle.east,
+            Direction::SOUTH => &puzzle.south,
+            Direction::WEST => &puzzle.west,
+        };
+        for (index, hint) in hints.iter().enumerate() {
+            let hint = match *hint {
+                Some(x) => x,
+                None => continue,
+            };
+            // The towers in the order they're seen from that side.
+            let mut line = match side {
+                Direction::NORTH | Direction::SOUTH => column(candidate, index),
+                Direction::EAST | Direction::WEST => row(candidate, index),
+            };
+            if side == Direction::EAST || side == Direction::SOUTH {
+                line.reverse();
+            }
+            let seen = calculate_view(&line);
+            if seen != hint {
+                return Err(Violation::View { side, index, hint, seen });
+            }
+        }
+    }
+    return Ok(());
+}
@@ -0,0 +1,82 @@
+// Checks that making puzzles in a batch, over several threads, gives the same puzzles as making them
+// one after the other.
+
+use puzzle_solvers::grader::Tier;
+use puzzle_solvers::latin::maker::Backend;
+use puzzle_solvers::maker;
+use puzzle_solvers::maker::Progress;
+use puzzle_solvers::towers;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// Hard towers puzzles, so that some attempts end up too easy and are retried.
+fn make(rng: &mut StdRng) -> Result<towers::puzzle::Puzzle, String> {
+    let hard = Tier::Hard..=Tier::Hard;
+    return towers::maker::make_puzzle_in_tiers(4, Backend::RowByRow, &hard, rng).ok_or(String::from("too easy"));
+}
+
+// What make_batch should give: puzzle i made from the seed first_seed + i, retrying with the same
+// generator, and how many attempts were rejected.
+fn make_in_order(count: usize, first_seed: u64) -> (Vec<(u64, towers::puzzle::Puzzle)>, usize) {
+    let mut puzzles = Vec::new();
+    let mut rejected = 0;
+    for i in 0..count {
+        let seed = first_seed.wrapping_add(i as u64);
+        let mut rng = StdRng::seed_from_u64(seed);
+        loop {
+            match make(&mut rng) {
+                Ok(p) => { puzzles.push((seed, p)); break; },
+                Err(_) => { rejected += 1; },
+            }
+        }
+    }
+    return (puzzles, rejected);
+}
+
+#[test]
+fn batch_matches_sequential() {
+    for first_seed in [7, u64::MAX - 2] {
+        let (expected, rejected) = make_in_order(6, first_seed);
+        assert!(rejected > 0, "the seeds should need some retries");
+        let mut progress = Vec::new();
+        let batch = maker::make_batch(6, first_seed, 1000, make, |x| progress.push(x)).unwrap();
+        assert_eq!(batch, expected);
+
+        // Every puzzle is reported made once, with the count of puzzles made so far.
+        let mut made: Vec<usize> = progress.iter().filter_map(|x| match x {
+            Progress::Made { index, seed, total, .. } => {
+                assert_eq!((*seed, *total), (expected[*index].0, 6));
+                Some(*index)
+            },
+            Progress::Rejected { .. } => None,
+        }).collect();
+        let counts: Vec<usize> = progress.iter().filter_map(|x| match x {
+            Progress::Made { made, .. } => Some(*made),
+            Progress::Rejected { .. } => None,
+        }).collect();
+        assert_eq!(counts, (1..=6).collect::<Vec<usize>>());
+        made.sort();
+        assert_eq!(made, (0..6).collect::<Vec<usize>>());
+        assert_eq!(progress.len() - 6, rejected);
+    }
+}
+
+#[test]
+fn empty_batch() {
+    let batch = maker::make_batch(0, 1, 1000, make, |_| panic!("nothing to report")).unwrap();
+    assert!(batch.is_empty());
+}
+
+// A puzzle that is never made is given up after the allowed attempts, instead of retried forever.
+#[test]
+fn gives_up() {
+    let never = |_: &mut StdRng| -> Result<u8, String> { Err(String::from("never")) };
+    let mut rejected = 0;
+    let batch = maker::make_batch(1, 10, 5, never, |_| rejected += 1);
+    assert_eq!(batch, Err(String::from("Gave up on the puzzle with seed 10 after 5 rejected attempts")));
+    assert_eq!(rejected, 5);
+
+    // The other threads stop too.
+    assert!(maker::make_batch(20, 10, 5, never, |_| {}).is_err());
+}
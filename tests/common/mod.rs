@@ -0,0 +1,26 @@
+// Reading the corpus files, shared by the test files that check puzzles from them.
+
+// Each test file is its own crate and only uses some of these.
+#![allow(dead_code)]
+
+use puzzle_solvers::corpus;
+use puzzle_solvers::corpus::Entry;
+use puzzle_solvers::puzzle::Puzzle;
+
+use std::fs;
+
+// The content of a file, relative to the root of the crate.
+pub fn read(file: &str) -> String {
+    let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file);
+    return fs::read_to_string(&path).unwrap_or_else(|e| panic!("could not read {}: {}", path, e));
+}
+
+// The entries of a corpus file, relative to the root of the crate.
+pub fn corpus_entries(file: &str) -> Vec<Entry> {
+    return corpus::read_entries(&read(file));
+}
+
+// The puzzles of a corpus file, relative to the root of the crate.
+pub fn corpus_puzzles<P: Puzzle>(file: &str) -> Vec<P> {
+    return corpus_entries(file).iter().map(|e| P::from_string(&e.body).unwrap()).collect();
+}
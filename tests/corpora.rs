@@ -0,0 +1,112 @@
+// Solves every puzzle of the checked-in corpora and checks that it still has exactly one solution,
+// that the solution respects the rules of the puzzle (checked by the puzzle, not the solver) and
+// that it takes as many nested guesses as the "Difficulty" recorded with it.
+//
+// The towers and loopy solvers the corpora were generated with picked their guesses in HashSet
+// order, so for some puzzles the depth they needed changed from run to run. Those puzzles are
+// recorded as "(varies)" and only checked for their solution. The others needed the same depth on
+// every run of those solvers (70 runs, or 23 for the one towers_8 puzzle), which is checked. That
+// is also what the latin_8 puzzle and three of the loopy_10 puzzles are recorded with, instead of
+// the depth they were generated with, which those solvers never needed in 10 and 70 runs.
+//
+// All of towers_8 and towers_9 take minutes even in release builds (towers_9 about 45 minutes on
+// one core), so only a sample of them is checked by default. Check all of them with
+// `cargo test --release --test corpora -- --ignored`.
+
+mod common;
+
+use puzzle_solvers::latin;
+use puzzle_solvers::loopy;
+use puzzle_solvers::puzzle::Puzzle;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::towers;
+
+// Checks every puzzle of the corpus file, relative to the root of the crate, and returns a
+// description of every problem found.
+fn check<S: PuzzleSolver>(file: &str) -> Vec<String> {
+    return check_some::<S>(file, |_| true);
+}
+
+// Checks the puzzles of the corpus file whose index, from 0, is kept.
+fn check_some<S: PuzzleSolver>(file: &str, keep: impl Fn(usize) -> bool) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    for (i, entry) in common::corpus_entries(file).iter().enumerate().filter(|(i, _)| keep(*i)) {
+        let p = match S::Puzzle::from_string(&entry.body) {
+            Ok(p) => p,
+            Err(e) => {
+                problems.push(format!("{} #{}: {}", file, i + 1, e));
+                continue;
+            }
+        };
+        let mut s = S::new(p.clone());
+        let solutions = s.full_solve(0, false);
+        if solutions.len() != 1 {
+            problems.push(format!("{} #{}: {} solutions\n{}", file, i + 1, solutions.len(), entry.body));
+            continue;
+        }
+        match solutions[0].solution() {
+            Some(solution) if p.is_solution(&solution) => {},
+            Some(_) => problems.push(format!("{} #{}: the solution found breaks the rules\n{}", file, i + 1, solutions[0].to_string())),
+            None => problems.push(format!("{} #{}: the solver didn't fill in the puzzle\n{}", file, i + 1, solutions[0].to_string())),
+        }
+        match entry.difficulty {
+            Some(_) if entry.difficulty_varies => {},
+            Some(d) if s.depth_needed() != d => problems.push(format!("{} #{}: recorded difficulty {}, solved with {} nested guesses", file, i + 1, d, s.depth_needed())),
+            Some(_) => {},
+            None => problems.push(format!("{} #{}: no difficulty recorded", file, i + 1)),
+        }
+    }
+    return problems;
+}
+
+fn assert_all_valid(problems: Vec<String>) {
+    assert!(problems.is_empty(), "{} problems:\n{}", problems.len(), problems.join("\n"));
+}
+
+#[test]
+fn latin_corpora() {
+    let mut problems = Vec::new();
+    for size in [2, 3, 4, 5, 6, 7, 8, 9, 12] {
+        problems.extend(check::<latin::solver::Solver>(&format!("latin/latin_{}.txt", size)));
+    }
+    assert_all_valid(problems);
+}
+
+#[test]
+fn towers_corpora() {
+    let mut problems = Vec::new();
+    for size in 2..=7 {
+        problems.extend(check::<towers::solver::Solver>(&format!("towers_{}.txt", size)));
+    }
+    assert_all_valid(problems);
+}
+
+// Every 20th puzzle of towers_8, and the two of the same sample of towers_9 that take seconds
+// instead of minutes in debug builds.
+#[test]
+fn towers_8_and_9_sample() {
+    let mut problems = check_some::<towers::solver::Solver>("towers_8.txt", |i| i % 20 == 0);
+    problems.extend(check_some::<towers::solver::Solver>("towers_9.txt", |i| i == 0 || i == 40));
+    assert_all_valid(problems);
+}
+
+#[test]
+#[ignore]
+fn towers_8_corpus() {
+    assert_all_valid(check::<towers::solver::Solver>("towers_8.txt"));
+}
+
+#[test]
+#[ignore]
+fn towers_9_corpus() {
+    assert_all_valid(check::<towers::solver::Solver>("towers_9.txt"));
+}
+
+#[test]
+fn loopy_corpora() {
+    let mut problems = Vec::new();
+    for size in 2..=10 {
+        problems.extend(check::<loopy::solver::Solver>(&format!("loopy/loopy_{}.txt", size)));
+    }
+    assert_all_valid(problems);
+}
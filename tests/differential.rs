@@ -0,0 +1,119 @@
+// Compares the solvers with the brute-force reference solvers on random small puzzles: they must
+// agree on whether a puzzle has no solution, one or several, and on the solution when there's one.
+// The puzzles are made by removing random hints from puzzles with a known solution, and sometimes
+// changing one of the hints left, so every verdict comes up.
+//
+// DIFFERENTIAL_PUZZLES sets how many puzzles of each family to try (100 by default) and
+// DIFFERENTIAL_SEED the seed they're made from (0 by default), to look for disagreements beyond the
+// ones checked every time, e.g.
+// `DIFFERENTIAL_PUZZLES=10000 DIFFERENTIAL_SEED=7 cargo test --release --test differential`.
+
+use puzzle_solvers::latin;
+use puzzle_solvers::latin::maker::Backend;
+use puzzle_solvers::loopy;
+use puzzle_solvers::puzzle::Puzzle;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::towers;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use std::env;
+
+fn setting(name: &str, default: u64) -> u64 {
+    return match env::var(name) {
+        Ok(x) => x.parse().expect("should be a number"),
+        Err(_) => default,
+    };
+}
+
+// Keeps each hint of the puzzle with a random probability.
+fn remove_random_hints<P: Puzzle, R: Rng>(p: &P, rng: &mut R) -> P {
+    let keep: f64 = rng.gen_range(0.2..0.9);
+    let hints_to_remove: Vec<bool> = (0..p.number_of_hints()).map(|_| !rng.gen_bool(keep)).collect();
+    return p.with_hints_removed(&hints_to_remove, 0);
+}
+
+// Solves every puzzle with the solver and the reference solver, and returns a description of every
+// puzzle they disagree on.
+fn compare<S, M, F>(family: &str, make: M, reference: F) -> Vec<String>
+where
+    S: PuzzleSolver,
+    M: Fn(&mut StdRng) -> S::Puzzle,
+    F: Fn(&S::Puzzle) -> Vec<<S::Puzzle as Puzzle>::Solution>,
+{
+    let count = setting("DIFFERENTIAL_PUZZLES", 100);
+    let mut rng = StdRng::seed_from_u64(setting("DIFFERENTIAL_SEED", 0));
+    let mut problems: Vec<String> = Vec::new();
+    for i in 0..count {
+        let p = make(&mut rng);
+        let solutions = S::new(p.clone()).full_solve(0, false);
+        let expected = reference(&p);
+        if verdict(solutions.len()) != verdict(expected.len()) {
+            problems.push(format!("{} #{}: {} solutions instead of {}\n{}", family, i, solutions.len(), expected.len(), p.to_string()));
+        } else if solutions.len() == 1 && solutions[0].solution().as_ref() != Some(&expected[0]) {
+            problems.push(format!("{} #{}: wrong solution\n{}\n{}", family, i, p.to_string(), solutions[0].to_string()));
+        }
+    }
+    return problems;
+}
+
+// The solvers may return more than two solutions, but only whether there are none, one or several
+// matters.
+fn verdict(number_of_solutions: usize) -> usize {
+    return number_of_solutions.min(2);
+}
+
+fn assert_agree(problems: Vec<String>) {
+    assert!(problems.is_empty(), "{} disagreements:\n{}", problems.len(), problems.join("\n"));
+}
+
+fn random_latin(rng: &mut StdRng) -> latin::puzzle::Puzzle {
+    let n = rng.gen_range(2..=5);
+    let mut p = remove_random_hints(&latin::maker::random_filled(n, Backend::RowByRow, rng), rng);
+    if rng.gen_bool(0.25) {
+        let (i, j) = (rng.gen_range(0..p.size), rng.gen_range(0..p.size));
+        p.grid[i][j] = Some(rng.gen_range(0..n));
+    }
+    return p;
+}
+
+#[test]
+fn latin_agrees_with_reference() {
+    assert_agree(compare::<latin::solver::Solver, _, _>("latin", random_latin, |p| latin::reference::solutions(p, 2)));
+}
+
+fn random_towers(rng: &mut StdRng) -> towers::puzzle::Puzzle {
+    let n = rng.gen_range(2..=5);
+    let full = towers::puzzle::Puzzle::from_latin_with_view_hints(latin::maker::random_filled(n, Backend::RowByRow, rng), 0);
+    let mut p = remove_random_hints(&full, rng);
+    if rng.gen_bool(0.25) {
+        let side = match rng.gen_range(0..4) {
+            0 => &mut p.north,
+            1 => &mut p.east,
+            2 => &mut p.south,
+            _ => &mut p.west,
+        };
+        side[rng.gen_range(0..n as usize)] = Some(rng.gen_range(1..=n));
+    }
+    return p;
+}
+
+#[test]
+fn towers_agrees_with_reference() {
+    assert_agree(compare::<towers::solver::Solver, _, _>("towers", random_towers, |p| towers::reference::solutions(p, 2)));
+}
+
+fn random_loopy(rng: &mut StdRng) -> loopy::puzzle::Puzzle {
+    let (width, height) = (rng.gen_range(1..=4), rng.gen_range(1..=4));
+    let mut p = remove_random_hints(&loopy::maker::make_puzzle(width, height, rng), rng);
+    if rng.gen_bool(0.25) {
+        p.grid[rng.gen_range(0..height)][rng.gen_range(0..width)] = Some(rng.gen_range(0..=3));
+    }
+    return p;
+}
+
+#[test]
+fn loopy_agrees_with_reference() {
+    assert_agree(compare::<loopy::solver::Solver, _, _>("loopy", random_loopy, |p| loopy::reference::solutions(p, 2)));
+}
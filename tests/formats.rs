@@ -0,0 +1,179 @@
+// Checks that the puzzles read back what they write, in every format they support.
+
+mod common;
+
+use puzzle_solvers::latin;
+use puzzle_solvers::loopy;
+use puzzle_solvers::loopy::puzzle::Solution;
+use puzzle_solvers::parse::ParseError;
+use puzzle_solvers::parse::Reason;
+use puzzle_solvers::towers;
+
+// A 3 by 3 latin puzzle with values from 0 to 2 and a few cells left empty.
+fn latin_puzzle() -> latin::puzzle::Puzzle {
+    let grid = vec![
+        vec![Some(0), None, Some(2)],
+        vec![None, Some(2), None],
+        vec![Some(2), Some(0), None],
+    ];
+    return latin::puzzle::Puzzle { size: 3, grid, difficulty: 0 };
+}
+
+#[test]
+fn latin_human_round_trip() {
+    let p = latin_puzzle();
+    let s = p.to_human_string();
+    assert_eq!(s, "0·2\n·2·\n20·");
+    assert_eq!(latin::puzzle::Puzzle::from_human_string(&s), Ok(p));
+}
+
+fn towers_puzzle() -> towers::puzzle::Puzzle {
+    return towers::puzzle::Puzzle {
+        latin: latin_puzzle(),
+        north: vec![Some(3), None, None],
+        east: vec![None, None, Some(2)],
+        south: vec![None, Some(1), None],
+        west: vec![Some(1), None, None],
+        difficulty: 0,
+    };
+}
+
+#[test]
+fn towers_human_round_trip() {
+    let p = towers_puzzle();
+    let s = p.to_human_string();
+    assert_eq!(towers::puzzle::Puzzle::from_human_string(&s), Ok(p));
+}
+
+// The grid of a towers puzzle is written the same way as a latin puzzle, so values are 0-indexed in
+// both.
+#[test]
+fn latin_and_towers_grids_agree() {
+    let p = towers::puzzle::Puzzle::from_latin_with_view_hints(latin_puzzle(), 0);
+    let towers_rows: Vec<String> = p.to_human_string().lines().skip(1).take(3).map(|l| l.chars().skip(1).take(3).collect()).collect();
+    let latin_rows: Vec<String> = latin_puzzle().to_human_string().replace('·', "?").lines().map(String::from).collect();
+    assert_eq!(towers_rows, latin_rows);
+}
+
+// Every latin corpus puzzle is written back exactly as it was read.
+#[test]
+fn latin_tatham_round_trip() {
+    for n in [2, 3, 4, 8, 12] {
+        for entry in common::corpus_entries(&format!("latin/latin_{}.txt", n)) {
+            let p = latin::puzzle::Puzzle::from_tatham_string(&entry.body).unwrap();
+            assert_eq!(p.to_tatham_string(), entry.body);
+        }
+    }
+}
+
+#[test]
+fn towers_tatham_round_trip() {
+    let p = towers_puzzle();
+    let s = p.to_tatham_string();
+    assert_eq!(s, "3:3////1//1/////2,1a3a3a3_1a");
+    assert_eq!(towers::puzzle::Puzzle::from_tatham_string(&s), Ok(p));
+
+    // The game IDs of the old towers files, with every cell left empty.
+    for n in [2, 3] {
+        for line in common::read(&format!("towers/towers_{}.txt", n)).lines() {
+            let p = towers::puzzle::Puzzle::from_tatham_string(line).unwrap();
+            assert_eq!(p.to_tatham_string(), line);
+        }
+    }
+}
+
+// Tatham's Towers writes the cell hints only if there are some, so its game IDs can stop after the
+// view hints.
+#[test]
+fn towers_tatham_without_grid() {
+    let expected = towers::puzzle::Puzzle::from_tatham_string("4:2/1/2/3/2/3/2/1/3/2/1/2/1/2/3/2,p").unwrap();
+    assert_eq!(towers::puzzle::Puzzle::from_tatham_string("4:2/1/2/3/2/3/2/1/3/2/1/2/1/2/3/2"), Ok(expected.clone()));
+    assert_eq!(towers::puzzle::Puzzle::from_tatham_string("4:2/1/2/3/2/3/2/1/3/2/1/2/1/2/3/2,"), Ok(expected.clone()));
+    assert_eq!(expected.north, vec![Some(2), Some(1), Some(2), Some(3)]);
+    assert_eq!(expected.south, vec![Some(2), Some(3), Some(2), Some(1)]);
+    assert_eq!(expected.west, vec![Some(3), Some(2), Some(1), Some(2)]);
+    assert_eq!(expected.east, vec![Some(1), Some(2), Some(3), Some(2)]);
+    assert_eq!(expected.latin.grid, vec![vec![None; 4]; 4]);
+}
+
+fn assert_same_loopy(p: &loopy::puzzle::Puzzle, q: &loopy::puzzle::Puzzle) {
+    assert_eq!((p.width, p.height, &p.grid), (q.width, q.height, &q.grid));
+}
+
+// Every loopy corpus puzzle reads back from its game ID.
+#[test]
+fn loopy_tatham_round_trip() {
+    for n in 2..=10 {
+        for entry in common::corpus_entries(&format!("loopy/loopy_{}.txt", n)) {
+            let p = loopy::puzzle::Puzzle::from_string(&entry.body).unwrap();
+            let id = p.to_tatham_string();
+            assert!(id.starts_with(&format!("{}x{}t0:", n, n)));
+            assert_same_loopy(&loopy::puzzle::Puzzle::from_tatham_string(&id).unwrap(), &p);
+        }
+    }
+}
+
+#[test]
+fn loopy_tatham_game_ids() {
+    let p = loopy::puzzle::Puzzle::from_tatham_string("4x4t0:a2b13e3a0b").unwrap();
+    assert_eq!(p.grid, vec![
+        vec![None, Some(2), None, None],
+        vec![Some(1), Some(3), None, None],
+        vec![None, None, None, Some(3)],
+        vec![None, Some(0), None, None],
+    ]);
+    assert_eq!(p.to_tatham_string(), "4x4t0:a2b13e3a0b");
+
+    // The size alone, or with Loopy's difficulty setting, means the same grid.
+    assert_same_loopy(&loopy::puzzle::Puzzle::from_tatham_string("4:a2b13e3a0b").unwrap(), &p);
+    assert_same_loopy(&loopy::puzzle::Puzzle::from_tatham_string("4x4t0de:a2b13e3a0b").unwrap(), &p);
+
+    // Runs of more than 26 empty cells take several letters.
+    let empty = loopy::puzzle::Puzzle::from_tatham_string("7x4t0:zb").unwrap();
+    assert_eq!((empty.width, empty.height), (7, 4));
+    assert_eq!(empty.grid, vec![vec![None; 7]; 4]);
+    assert_eq!(empty.to_tatham_string(), "7x4t0:zb");
+}
+
+// The loop around the top left cell of a 2 by 1 grid.
+fn loopy_solution() -> Solution {
+    return Solution { horizontal: vec![vec![true, false], vec![true, false]], vertical: vec![vec![true, true, false]] };
+}
+
+#[test]
+fn loopy_solution_text() {
+    let s = loopy_solution().to_string();
+    assert_eq!(s, "2x1\n10\n10\n110");
+    assert_eq!(Solution::from_string(&s), Ok(loopy_solution()));
+    assert_eq!(Solution::from_string("2x1\n10\n10\n11"), Err(ParseError::new(4, 3, Reason::WrongRowLength { expected: 3, found: 2 })));
+    assert_eq!(Solution::from_string("2x1\n10\n10"), Err(ParseError::new(4, 1, Reason::WrongHintCount { expected: 3, found: 2 })));
+}
+
+#[test]
+fn loopy_solution_json() {
+    assert_eq!(
+        loopy_solution().to_json(),
+        "{\"width\": 2, \"height\": 1, \"loop\": [[0, 0], [0, 1], [1, 1], [1, 0]], \"horizontal\": [[true, false], [true, false]], \"vertical\": [[true, true, false]]}",
+    );
+    let nothing = Solution { horizontal: vec![vec![false]; 2], vertical: vec![vec![false; 2]] };
+    assert_eq!(nothing.to_json(), "{\"width\": 1, \"height\": 1, \"loop\": [], \"horizontal\": [[false], [false]], \"vertical\": [[false, false]]}");
+}
+
+// The solutions of the loopy_5 corpus read back from their text, and their loop goes through every
+// edge that's on, one edge at a time.
+#[test]
+fn loopy_solved_corpus() {
+    for entry in common::corpus_entries("loopy/loopy_5.txt") {
+        let p = loopy::puzzle::Puzzle::from_string(&entry.body).unwrap();
+        let solution = loopy::solver::Solver::new(p).full_solve(0, false)[0].solution().unwrap();
+        assert_eq!(Solution::from_string(&solution.to_string()), Ok(solution.clone()));
+
+        let nodes = solution.loop_nodes();
+        let on = solution.horizontal.iter().chain(solution.vertical.iter()).flatten().filter(|e| **e).count();
+        assert_eq!(nodes.len(), on);
+        for k in 0..nodes.len() {
+            let (a, b) = (nodes[k], nodes[(k + 1) % nodes.len()]);
+            assert!(solution.neighbours(a.0, a.1).contains(&b));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// Checks that puzzles are graded by the hardest technique their solver needed, and that known
+// puzzles keep their grade.
+
+mod common;
+
+use puzzle_solvers::grader;
+use puzzle_solvers::grader::Grade;
+use puzzle_solvers::grader::Tier;
+use puzzle_solvers::latin;
+use puzzle_solvers::loopy;
+use puzzle_solvers::loopy::solver::trace::Rule;
+use puzzle_solvers::puzzle::Puzzle;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::towers;
+
+#[test]
+fn grade_counts_techniques() {
+    let g = grader::grade(vec![Rule::CornerEntry, Rule::ZeroHint, Rule::CornerEntry, Rule::CellCount]);
+    assert_eq!(g.tier, Tier::Normal);
+    assert_eq!(g.hardest, Some(String::from("CornerEntry")));
+    assert_eq!(g.counts, vec![
+        (String::from("ZeroHint"), 1),
+        (String::from("CellCount"), 1),
+        (String::from("CornerEntry"), 2),
+    ]);
+    assert_eq!(g.to_string(), "Normal (hardest: CornerEntry; ZeroHint 1, CellCount 1, CornerEntry 2)");
+}
+
+// The hardest technique is the one with the hardest tier, even if it comes earlier in the list.
+#[test]
+fn grade_by_tier() {
+    let g = grader::grade(vec![Rule::OuterInnerBorder, Rule::HintBetweenSimilarCells]);
+    assert_eq!((g.tier, g.hardest), (Tier::Extreme, Some(String::from("OuterInnerBorder"))));
+}
+
+#[test]
+fn grade_nothing() {
+    let g = grader::grade(Vec::<Rule>::new());
+    assert_eq!(g, Grade { tier: Tier::Easy, hardest: None, counts: vec![] });
+    assert_eq!(g.to_string(), "Easy");
+}
+
+#[test]
+fn tier_names() {
+    for t in Tier::all() {
+        assert_eq!(t.to_string().to_lowercase().parse::<Tier>(), Ok(t));
+    }
+    assert!("medium".parse::<Tier>().is_err());
+}
+
+// The grade of the first puzzle of a corpus file.
+fn first_grade<S: PuzzleSolver>(file: &str) -> Grade {
+    let p = S::Puzzle::from_string(&common::corpus_entries(file)[0].body).unwrap();
+    let solutions = S::new(p).full_solve(0, false);
+    assert_eq!(solutions.len(), 1, "{} should have a unique solution", file);
+    return solutions[0].grade();
+}
+
+fn tier_and_hardest(g: Grade) -> (Tier, String) {
+    return (g.tier, g.hardest.unwrap());
+}
+
+#[test]
+fn known_grades() {
+    let easy = (Tier::Easy, String::from("HiddenSingleInColumn"));
+    assert_eq!(tier_and_hardest(first_grade::<latin::solver::Solver>("latin/latin_4.txt")), easy);
+    let guess = (Tier::Unreasonable, String::from("Guess"));
+    assert_eq!(tier_and_hardest(first_grade::<latin::solver::Solver>("latin/latin_12.txt")), guess);
+    let views = (Tier::Hard, String::from("ViewAnalysis"));
+    assert_eq!(tier_and_hardest(first_grade::<towers::solver::Solver>("towers_4.txt")), views);
+    let trial = (Tier::Unreasonable, String::from("TrialAndError"));
+    assert_eq!(tier_and_hardest(first_grade::<towers::solver::Solver>("towers_5.txt")), trial);
+    let border = (Tier::Extreme, String::from("OuterInnerBorder"));
+    assert_eq!(tier_and_hardest(first_grade::<loopy::solver::Solver>("loopy/loopy_5.txt")), border);
+}
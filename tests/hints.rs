@@ -0,0 +1,59 @@
+// Checks that the hints given by the latin and towers solvers are true of the solution, and that
+// there's no hint to give for a puzzle that's already solved or can't be solved.
+
+mod common;
+
+use common::corpus_puzzles;
+use puzzle_solvers::latin;
+use puzzle_solvers::towers;
+
+#[test]
+fn latin_hints_hold() {
+    for p in corpus_puzzles::<latin::puzzle::Puzzle>("latin/latin_4.txt") {
+        let solution = latin::solver::Solver::new(p.clone()).full_solve(0, false)[0].solution().unwrap();
+        let hint = latin::solver::Solver::new(p.clone()).next_hint().expect("should have a hint");
+        let t = hint.fact.t;
+        let is_value = solution[t.row as usize][t.col as usize] == t.val;
+        assert_eq!(is_value, !hint.fact.negated, "{:?} is wrong for\n{}", hint, p.to_human_string());
+    }
+}
+
+#[test]
+fn latin_no_hints() {
+    let grid = [[0, 1, 2], [1, 2, 0], [2, 0, 1]].iter().map(|r| r.iter().map(|x| Some(*x)).collect()).collect();
+    let solved = latin::puzzle::Puzzle { size: 3, grid, difficulty: 0 };
+    assert_eq!(latin::solver::Solver::new(solved).next_hint(), None);
+
+    let mut grid = vec![vec![None; 3]; 3];
+    grid[0][0] = Some(1);
+    grid[0][2] = Some(1);
+    let contradictory = latin::puzzle::Puzzle { size: 3, grid, difficulty: 0 };
+    assert_eq!(latin::solver::Solver::new(contradictory).next_hint(), None);
+}
+
+#[test]
+fn towers_hints_hold() {
+    for p in corpus_puzzles::<towers::puzzle::Puzzle>("towers_4.txt") {
+        let solution = towers::solver::Solver::new(p.clone()).full_solve(0, false)[0].solution().unwrap();
+        let hint = towers::solver::Solver::new(p.clone()).next_hint().expect("should have a hint");
+        let value = solution[hint.row][hint.column];
+        assert!(!hint.removed.contains(&value), "{:?} is wrong for\n{}", hint, p.to_human_string());
+        if let Some(x) = hint.solved {
+            assert_eq!(x, value, "{:?} is wrong for\n{}", hint, p.to_human_string());
+        }
+    }
+}
+
+#[test]
+fn towers_no_hints() {
+    let grid = [[0, 1, 2], [1, 2, 0], [2, 0, 1]].iter().map(|r| r.iter().map(|x| Some(*x)).collect()).collect();
+    let latin = latin::puzzle::Puzzle { size: 3, grid, difficulty: 0 };
+    let solved = towers::puzzle::Puzzle::from_latin_with_view_hints(latin, 0);
+    assert_eq!(towers::solver::Solver::new(solved).next_hint(), None);
+
+    // Both ends of the top row see a single tower, so the tallest would have to be at both ends.
+    let mut contradictory = towers::puzzle::Puzzle::from_tatham_string("3:///////////,i").unwrap();
+    contradictory.west[0] = Some(1);
+    contradictory.east[0] = Some(1);
+    assert_eq!(towers::solver::Solver::new(contradictory).next_hint(), None);
+}
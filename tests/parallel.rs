@@ -0,0 +1,84 @@
+// Checks that searching in parallel gives the same verdict and depth as searching one guess after
+// the other, and that it stops once two solutions are found.
+
+mod common;
+
+use common::corpus_puzzles;
+use puzzle_solvers::grader::Grade;
+use puzzle_solvers::latin;
+use puzzle_solvers::loopy;
+use puzzle_solvers::puzzle::Puzzle;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::solver::Status;
+use puzzle_solvers::towers;
+
+// The status and depth needed of solving p, and the grade of its solution.
+fn outcome<S: PuzzleSolver>(p: S::Puzzle, parallel_depth: u8) -> (Status, u8, Option<Grade>) {
+    let mut s = S::new(p);
+    if parallel_depth > 0 {
+        s.search_in_parallel(parallel_depth);
+    }
+    let solutions = s.full_solve(0, false);
+    let grade = match solutions.len() {
+        1 => Some(solutions[0].grade()),
+        _ => None,
+    };
+    return (s.status(), s.depth_needed(), grade);
+}
+
+fn assert_same_as_sequential<S: PuzzleSolver>(file: &str, count: usize) {
+    for p in corpus_puzzles::<S::Puzzle>(file).into_iter().take(count) {
+        assert_puzzle_same_as_sequential::<S>(file, p);
+    }
+}
+
+fn assert_puzzle_same_as_sequential<S: PuzzleSolver>(file: &str, p: S::Puzzle) {
+    let expected = outcome::<S>(p.clone(), 0);
+    for parallel_depth in 1..=2 {
+        assert_eq!(outcome::<S>(p.clone(), parallel_depth), expected, "{} at parallel depth {}:\n{}", file, parallel_depth, p.to_string());
+    }
+}
+
+#[test]
+fn latin_same_as_sequential() {
+    assert_same_as_sequential::<latin::solver::Solver>("latin/latin_12.txt", 1);
+
+    // With its values reversed, the first guess the latin_12 puzzle needs leads to a contradiction
+    // instead of to the solution, so the solution is found with the opposite of the guess.
+    let mut p = corpus_puzzles::<latin::puzzle::Puzzle>("latin/latin_12.txt").remove(0);
+    p.grid = p.grid.iter().map(|row| row.iter().map(|x| x.map(|v| 11 - v)).collect()).collect();
+    assert_puzzle_same_as_sequential::<latin::solver::Solver>("latin/latin_12.txt reversed", p);
+}
+
+#[test]
+fn towers_same_as_sequential() {
+    assert_same_as_sequential::<towers::solver::Solver>("towers_5.txt", 30);
+    assert_same_as_sequential::<towers::solver::Solver>("towers_6.txt", 15);
+}
+
+#[test]
+fn loopy_same_as_sequential() {
+    assert_same_as_sequential::<loopy::solver::Solver>("loopy/loopy_6.txt", 10);
+}
+
+// Every latin square of order 6 solves a towers puzzle without hints, so the search has to give up
+// once it finds two of them instead of going through them all.
+#[test]
+fn towers_cancelled() {
+    let p = towers::puzzle::Puzzle::from_tatham_string("6:///////////////////////").unwrap();
+    let mut s = towers::solver::Solver::new(p);
+    s.search_in_parallel(2);
+    let mut copy = s.clone();
+    let solutions = s.full_solve(0, false);
+    assert_eq!(s.status(), Status::MultipleSolutions);
+    assert_eq!(solutions.len(), 2);
+    assert_ne!(solutions[0].solution(), solutions[1].solution());
+
+    // A copy sharing the search gives up as soon as it has to guess, with the solutions found
+    // already.
+    let copy_solutions = copy.full_solve(0, false);
+    assert_eq!(copy.status(), Status::MultipleSolutions);
+    assert_eq!(copy.depth_needed(), 0);
+    let found = |x: &[towers::solver::Solver]| x.iter().map(|s| s.solution()).collect::<Vec<_>>();
+    assert_eq!(found(&copy_solutions), found(&solutions));
+}
@@ -0,0 +1,92 @@
+// Checks that malformed puzzles are reported with the reason and the line and column of the
+// problem, like a text editor would show them.
+
+use puzzle_solvers::latin::puzzle::Puzzle as LatinPuzzle;
+use puzzle_solvers::loopy::puzzle::Puzzle as LoopyPuzzle;
+use puzzle_solvers::parse::ParseError;
+use puzzle_solvers::parse::Reason;
+use puzzle_solvers::towers::puzzle::Puzzle as TowersPuzzle;
+
+fn error(line: usize, column: usize, reason: Reason) -> Result<LatinPuzzle, ParseError> {
+    return Err(ParseError::new(line, column, reason));
+}
+
+#[test]
+fn human_grid_errors() {
+    assert_eq!(LatinPuzzle::from_human_string("012\n12\n201"), error(2, 3, Reason::WrongRowLength { expected: 3, found: 2 }));
+    assert_eq!(LatinPuzzle::from_human_string("012\n1200\n201"), error(2, 4, Reason::WrongRowLength { expected: 3, found: 4 }));
+    assert_eq!(LatinPuzzle::from_human_string("012\n120"), error(3, 1, Reason::NotSquare { rows: 2, columns: 3 }));
+    assert_eq!(LatinPuzzle::from_human_string("01\n12\n20"), error(3, 1, Reason::NotSquare { rows: 3, columns: 2 }));
+    assert_eq!(LatinPuzzle::from_human_string("012\n1·3\n201"), error(2, 3, Reason::ValueOutOfRange { value: 3, min: 0, max: 2 }));
+    assert_eq!(LatinPuzzle::from_human_string("012\n1x0\n201"), error(2, 2, Reason::BadCharacter('x')));
+    assert_eq!(LatinPuzzle::from_human_string(""), error(1, 1, Reason::Truncated));
+}
+
+// Blank lines and indentation are skipped, but still counted in the line and column reported.
+#[test]
+fn human_grid_positions() {
+    assert_eq!(LatinPuzzle::from_human_string("\n  012\n\n  1x0\n  201"), error(4, 4, Reason::BadCharacter('x')));
+}
+
+#[test]
+fn tatham_errors() {
+    assert_eq!(LatinPuzzle::from_tatham_string("3:a2b1c"), error(1, 8, Reason::Truncated));
+    assert_eq!(LatinPuzzle::from_tatham_string("3:i3"), error(1, 4, Reason::TooLong));
+    assert_eq!(LatinPuzzle::from_tatham_string("3:h4"), error(1, 4, Reason::ValueOutOfRange { value: 4, min: 1, max: 3 }));
+    assert_eq!(LatinPuzzle::from_tatham_string("3:a2!"), error(1, 5, Reason::BadCharacter('!')));
+    assert_eq!(LatinPuzzle::from_tatham_string("a2b1c"), error(1, 1, Reason::MissingSize));
+    assert_eq!(LatinPuzzle::from_tatham_string(":a2b1c"), error(1, 1, Reason::MissingSize));
+    assert_eq!(LatinPuzzle::from_tatham_string("x:i"), error(1, 1, Reason::BadCharacter('x')));
+}
+
+// The grid part of a game ID is parsed on its own, so its errors are shifted by whatever comes
+// before it.
+#[test]
+fn tatham_positions() {
+    assert_eq!(LatinPuzzle::from_tatham_string("  3:a2!"), error(1, 7, Reason::BadCharacter('!')));
+    assert_eq!(LatinPuzzle::from_tatham_string("12:a!"), error(1, 5, Reason::BadCharacter('!')));
+    assert_eq!(
+        TowersPuzzle::from_tatham_string("3:1//////////2/,a!").map(|_| ()),
+        Err(ParseError::new(1, 18, Reason::BadCharacter('!'))),
+    );
+    assert_eq!(
+        TowersPuzzle::from_tatham_string("3:1/////4//////").map(|_| ()),
+        Err(ParseError::new(1, 9, Reason::ValueOutOfRange { value: 4, min: 1, max: 3 })),
+    );
+    assert_eq!(
+        TowersPuzzle::from_tatham_string("3:1/2/3").map(|_| ()),
+        Err(ParseError::new(1, 3, Reason::WrongHintCount { expected: 12, found: 3 })),
+    );
+    assert_eq!(
+        LoopyPuzzle::from_tatham_string("2x2:a5b").map(|_| ()),
+        Err(ParseError::new(1, 6, Reason::ValueOutOfRange { value: 5, min: 0, max: 4 })),
+    );
+}
+
+#[test]
+fn shifted() {
+    let e = ParseError::new(2, 3, Reason::Truncated);
+    assert_eq!(e.clone().shifted(0), e);
+    assert_eq!(e.shifted(4), ParseError::new(2, 7, Reason::Truncated));
+}
+
+#[test]
+fn display() {
+    let e = ParseError::new(2, 3, Reason::ValueOutOfRange { value: 4, min: 1, max: 3 });
+    assert_eq!(e.to_string(), "line 2, column 3: value 4 is not between 1 and 3");
+    let e = ParseError::new(1, 1, Reason::WrongRowLength { expected: 3, found: 2 });
+    assert_eq!(e.to_string(), "line 1, column 1: row has 2 cells instead of 3");
+}
+
+// The latin solver keeps its candidates in 64 bit masks, so wider latin squares are rejected when
+// they are read instead of when they are solved. Towers puzzles don't use those masks.
+#[test]
+fn too_wide() {
+    let max = puzzle_solvers::latin::puzzle::MAX_SIZE;
+    assert_eq!(LatinPuzzle::from_tatham_string("65:a"), error(1, 1, Reason::ValueOutOfRange { value: 65, min: 1, max: 64 }));
+    let grid = vec![".".repeat(max + 1); max + 1].join("\n");
+    assert_eq!(LatinPuzzle::from_human_string(&grid), error(1, 1, Reason::TooWide { size: max + 1, max }));
+    let grid = vec![".".repeat(max); max].join("\n");
+    assert!(LatinPuzzle::from_human_string(&grid).is_ok());
+    assert!(TowersPuzzle::from_tatham_string(&format!("65:{}", "/".repeat(4 * 65 - 1))).is_ok());
+}
@@ -0,0 +1,81 @@
+// Checks the permutation iterator, ranking and counting against each other and against brute force.
+
+use puzzle_solvers::perm::counting::count_compatible;
+use puzzle_solvers::perm::permutation::PermIter;
+use puzzle_solvers::perm::ranking::rank;
+use puzzle_solvers::perm::ranking::unrank;
+
+fn factorial(n: u64) -> u64 {
+    return (1..=n).product();
+}
+
+// Every permutation of 0..n comes out once, in lexicographic order, and its rank is its place in
+// that order.
+#[test]
+fn ranks_follow_lexicographic_order() {
+    for n in 0..=6u8 {
+        let all: Vec<Vec<u8>> = PermIter::new(n, &vec![]).collect();
+        assert_eq!(all.len() as u64, factorial(n as u64));
+        for (i, p) in all.iter().enumerate() {
+            assert_eq!(rank(p), i as u64);
+            assert_eq!(&unrank(n, i as u64), p);
+        }
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+    }
+}
+
+#[test]
+fn rank_round_trip() {
+    let n = 20;
+    for r in [0, 1, 12345, 2432902008176639999, factorial(20) - 1] {
+        assert_eq!(rank(&unrank(n, r)), r);
+    }
+    assert_eq!(unrank(n, factorial(20) - 1), (0..n).rev().collect::<Vec<u8>>());
+}
+
+// The permutations of 0..4 that don't agree with 0123 anywhere are its 9 derangements.
+#[test]
+fn restrictions() {
+    let derangements: Vec<Vec<u8>> = PermIter::new(4, &vec![vec![0, 1, 2, 3]]).collect();
+    assert_eq!(derangements.len(), 9);
+    assert!(derangements.iter().all(|p| p.iter().enumerate().all(|(i, v)| i as u8 != *v)));
+
+    // Three rows of a latin square leave one way to finish it.
+    let rows = vec![vec![0, 1, 2, 3], vec![1, 2, 3, 0], vec![2, 3, 0, 1]];
+    assert_eq!(PermIter::new(4, &rows).collect::<Vec<_>>(), vec![vec![3, 0, 1, 2]]);
+}
+
+// Candidate sets for every position, built from the bits of mask.
+fn candidates_from_mask(n: usize, mask: u64) -> Vec<Vec<u8>> {
+    return (0..n).map(|i| (0..n as u8).filter(|v| mask & (1 << (i * n + *v as usize)) != 0).collect()).collect();
+}
+
+#[test]
+fn counts_match_brute_force() {
+    let n = 4;
+    let all: Vec<Vec<u8>> = PermIter::new(n as u8, &vec![]).collect();
+    // A fixed spread of masks over the 2^16 candidate sets of 4 positions.
+    for mask in (0..1u64 << (n * n)).step_by(97) {
+        let candidates = candidates_from_mask(n, mask);
+        let expected: Vec<Vec<u8>> = all.iter().filter(|p| (0..n).all(|i| candidates[i].contains(&p[i]))).cloned().collect();
+        assert_eq!(PermIter::with_candidates(&candidates).unwrap().collect::<Vec<_>>(), expected);
+        assert_eq!(count_compatible(&candidates), Ok(expected.len() as u64));
+    }
+}
+
+#[test]
+fn count_everything() {
+    for n in 0..=20 {
+        let candidates: Vec<Vec<u8>> = vec![(0..n as u8).collect(); n];
+        assert_eq!(count_compatible(&candidates), Ok(factorial(n as u64)));
+    }
+    assert!(count_compatible(&vec![(0..21).collect(); 21]).is_err());
+}
+
+#[test]
+fn candidates_out_of_range() {
+    let candidates = vec![vec![0, 1], vec![1, 2]];
+    let error = "candidate 2 of position 1 is not a value from 0 to 1";
+    assert_eq!(count_compatible(&candidates), Err(String::from(error)));
+    assert_eq!(PermIter::with_candidates(&candidates).map(|_| ()), Err(String::from(error)));
+}
@@ -0,0 +1,79 @@
+// Checks that puzzles generated for a tier grade in that tier, both from the library and from the
+// command line.
+
+use puzzle_solvers::grader::Tier;
+use puzzle_solvers::latin;
+use puzzle_solvers::latin::maker::Backend;
+use puzzle_solvers::loopy;
+use puzzle_solvers::solver::PuzzleSolver;
+use puzzle_solvers::towers;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+// Makes puzzles until count of them are in tiers, and returns the tier each one grades in.
+fn tiers_made<S: PuzzleSolver>(count: usize, mut make: impl FnMut(&mut StdRng) -> Option<S::Puzzle>) -> Vec<Tier> {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut tiers = Vec::new();
+    while tiers.len() < count {
+        if let Some(p) = make(&mut rng) {
+            let solutions = S::new(p).full_solve(0, false);
+            assert_eq!(solutions.len(), 1);
+            tiers.push(solutions[0].grade().tier);
+        }
+    }
+    return tiers;
+}
+
+#[test]
+fn latin_easy() {
+    let easy = Tier::Easy..=Tier::Easy;
+    let tiers = tiers_made::<latin::solver::Solver>(5, |rng| latin::maker::make_puzzle_in_tiers(4, Backend::RowByRow, &easy, rng));
+    assert_eq!(tiers, vec![Tier::Easy; 5]);
+}
+
+#[test]
+fn towers_easy() {
+    let easy = Tier::Easy..=Tier::Easy;
+    let tiers = tiers_made::<towers::solver::Solver>(5, |rng| towers::maker::make_puzzle_in_tiers(4, Backend::RowByRow, &easy, rng));
+    assert_eq!(tiers, vec![Tier::Easy; 5]);
+}
+
+// Loopy puzzles almost always need at least the Normal corner rules.
+#[test]
+fn loopy_normal() {
+    let normal = Tier::Normal..=Tier::Normal;
+    let tiers = tiers_made::<loopy::solver::Solver>(5, |rng| loopy::maker::make_puzzle_in_tiers(4, 4, &normal, rng));
+    assert_eq!(tiers, vec![Tier::Normal; 5]);
+}
+
+fn run(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_puzzle_solvers"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?} failed", args);
+    return String::from_utf8(output.stdout).unwrap();
+}
+
+// What `generate -g` writes grades in the tier asked for when read back by `grade`.
+#[test]
+fn generate_then_grade() {
+    for (family, tier) in [("latin", "easy"), ("towers", "easy"), ("loopy", "normal")] {
+        let puzzles = run(&["generate", "-t", family, "-s", "4", "-n", "3", "-g", tier, "-r", "1", "-q"], "");
+        let grades = run(&["grade", "-t", family, "-q", "-"], &puzzles);
+        let lines: Vec<&str> = grades.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", grades);
+        let expected = format!("Grade: {} ", tier.parse::<Tier>().unwrap());
+        for line in lines {
+            assert!(line.contains(&expected), "{} puzzle isn't {}: {}", family, tier, line);
+        }
+    }
+}
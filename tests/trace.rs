@@ -0,0 +1,46 @@
+// Checks that replaying the trace of a solved loopy puzzle decides every edge the way the solution
+// has it, and never decides an edge twice. The solver stops as soon as the loop is closed, so the
+// edges it never got to are off, like in the solution.
+
+mod common;
+
+use puzzle_solvers::loopy::puzzle::Puzzle;
+use puzzle_solvers::loopy::puzzle::Solution;
+use puzzle_solvers::loopy::solver::trace::replay;
+use puzzle_solvers::loopy::solver::trace::Board;
+use puzzle_solvers::loopy::solver::trace::Rule;
+use puzzle_solvers::loopy::solver::Solver;
+
+fn number_decided(board: &Board) -> usize {
+    return board.horizontal.iter().chain(board.vertical.iter()).flatten().filter(|e| e.is_some()).count();
+}
+
+// The edges decided on, with the undecided ones off.
+fn edges_on(board: &Board) -> Solution {
+    let on = |m: &Vec<Vec<Option<bool>>>| m.iter().map(|r| r.iter().map(|e| *e == Some(true)).collect()).collect();
+    return Solution { horizontal: on(&board.horizontal), vertical: on(&board.vertical) };
+}
+
+#[test]
+fn trace_replays_to_solution() {
+    let mut guesses = 0;
+    for n in [4, 6, 8] {
+        for (i, entry) in common::corpus_entries(&format!("loopy/loopy_{}.txt", n)).iter().enumerate() {
+            let p = Puzzle::from_string(&entry.body).unwrap();
+            let solved = &Solver::new(p.clone()).full_solve(0, false)[0];
+            let solution = solved.solution().unwrap();
+            guesses += solved.trace.iter().filter(|s| s.rule == Rule::Guess).count();
+
+            let mut board = Board::new(p.width, p.height);
+            for (k, step) in solved.trace.iter().enumerate() {
+                board.apply(step);
+                assert_eq!(number_decided(&board), k + 1, "loopy_{} #{}: step {} decides an edge again", n, i + 1, k + 1);
+                assert!(!step.premises.is_empty(), "loopy_{} #{}: step {} has no premise", n, i + 1, k + 1);
+            }
+            assert_eq!(edges_on(&board), solution, "loopy_{} #{}", n, i + 1);
+            assert_eq!(replay(p.width, p.height, &solved.trace), board, "loopy_{} #{}", n, i + 1);
+        }
+    }
+    // The trace of a solution also has the guesses that led to it.
+    assert!(guesses > 0);
+}
@@ -0,0 +1,55 @@
+// Checks that the uniform backend of latin::maker::random_filled gives every latin square of a small
+// order about equally often, with a chi-squared goodness of fit test against the uniform
+// distribution. There are 12 latin squares of order 3 and 576 of order 4.
+
+use puzzle_solvers::latin::maker::random_filled;
+use puzzle_solvers::latin::maker::Backend;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use std::collections::HashMap;
+
+fn is_latin_square(grid: &[Vec<Option<u8>>]) -> bool {
+    let n = grid.len();
+    for (i, row) in grid.iter().enumerate() {
+        let mut row: Vec<Option<u8>> = row.clone();
+        let mut column: Vec<Option<u8>> = grid.iter().map(|r| r[i]).collect();
+        row.sort();
+        column.sort();
+        let expected: Vec<Option<u8>> = (0..n as u8).map(Some).collect();
+        if row != expected || column != expected {
+            return false;
+        }
+    }
+    return true;
+}
+
+// The chi-squared statistic of samples squares of order n, assuming there are count squares.
+fn chi_squared(n: u8, count: usize, samples: usize, seed: u64) -> f64 {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seen: HashMap<Vec<Vec<Option<u8>>>, usize> = HashMap::new();
+    for _ in 0..samples {
+        let p = random_filled(n, Backend::Uniform, &mut rng);
+        assert!(is_latin_square(&p.grid), "not a latin square: {:?}", p.grid);
+        *seen.entry(p.grid).or_insert(0) += 1;
+    }
+    assert_eq!(seen.len(), count, "some latin squares of order {} were never generated", n);
+
+    let expected = samples as f64 / count as f64;
+    return seen.values().map(|x| (*x as f64 - expected).powi(2) / expected).sum();
+}
+
+#[test]
+fn order_3_is_uniform() {
+    // 11 degrees of freedom. The 99.9th percentile of the chi-squared distribution is 31.3.
+    let x = chi_squared(3, 12, 12 * 500, 1);
+    assert!(x < 31.3, "chi-squared statistic {} is too large", x);
+}
+
+#[test]
+fn order_4_is_uniform() {
+    // 575 degrees of freedom, so the statistic is close to normal with mean 575 and standard
+    // deviation 33.9. Allow 4 standard deviations.
+    let x = chi_squared(4, 576, 576 * 20, 1);
+    assert!(x < 575.0 + 4.0 * 33.9, "chi-squared statistic {} is too large", x);
+}
@@ -0,0 +1,103 @@
+// Checks that the verifiers of every family accept solutions and name the rule a wrong one breaks.
+
+use puzzle_solvers::latin;
+use puzzle_solvers::latin::verify::Violation as LatinViolation;
+use puzzle_solvers::loopy;
+use puzzle_solvers::loopy::puzzle::Solution;
+use puzzle_solvers::loopy::verify::Violation as LoopyViolation;
+use puzzle_solvers::towers;
+use puzzle_solvers::towers::solver::Direction;
+use puzzle_solvers::towers::verify::Violation as TowersViolation;
+
+// A 3 by 3 latin square with a 1 in the top left corner and a 3 in the middle.
+fn latin_puzzle() -> latin::puzzle::Puzzle {
+    let mut grid = vec![vec![None; 3]; 3];
+    grid[0][0] = Some(0);
+    grid[1][1] = Some(2);
+    return latin::puzzle::Puzzle { size: 3, grid, difficulty: 0 };
+}
+
+fn latin_square() -> Vec<Vec<u8>> {
+    return vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]];
+}
+
+#[test]
+fn latin() {
+    let p = latin_puzzle();
+    assert_eq!(latin::verify::verify(&p, &latin_square()), Ok(()));
+
+    assert_eq!(latin::verify::verify(&p, &[vec![0, 1, 2]]), Err(LatinViolation::WrongRowCount { expected: 3, found: 1 }));
+
+    let mut grid = latin_square();
+    grid[2].pop();
+    assert_eq!(latin::verify::verify(&p, &grid), Err(LatinViolation::WrongRowLength { row: 2, expected: 3, found: 2 }));
+
+    let mut grid = latin_square();
+    grid[2][1] = 5;
+    assert_eq!(latin::verify::verify(&p, &grid), Err(LatinViolation::ValueOutOfRange { row: 2, column: 1, value: 5 }));
+
+    let grid = vec![vec![1, 2, 0], vec![2, 0, 1], vec![0, 1, 2]];
+    assert_eq!(latin::verify::verify(&p, &grid), Err(LatinViolation::HintChanged { row: 0, column: 0, hint: 0, value: 1 }));
+
+    let mut grid = latin_square();
+    grid[2][2] = 0;
+    assert_eq!(latin::verify::verify(&p, &grid), Err(LatinViolation::RepeatedInRow { row: 2, value: 0, columns: (1, 2) }));
+
+    let grid = vec![vec![0, 1, 2], vec![1, 2, 0], vec![0, 1, 2]];
+    assert_eq!(latin::verify::verify(&p, &grid), Err(LatinViolation::RepeatedInColumn { column: 0, value: 0, rows: (0, 2) }));
+}
+
+#[test]
+fn towers() {
+    // Looking down the first column of the latin square sees all three towers, looking down the
+    // second sees two.
+    let mut p = towers::puzzle::Puzzle {
+        latin: latin_puzzle(),
+        north: vec![Some(3), None, None],
+        east: vec![None, None, Some(2)],
+        south: vec![None; 3],
+        west: vec![None; 3],
+        difficulty: 0,
+    };
+    assert_eq!(towers::verify::verify(&p, &latin_square()), Ok(()));
+
+    let mut grid = latin_square();
+    grid[2][2] = 0;
+    assert_eq!(towers::verify::verify(&p, &grid), Err(TowersViolation::Latin(LatinViolation::RepeatedInRow { row: 2, value: 0, columns: (1, 2) })));
+
+    p.north[1] = Some(3);
+    assert_eq!(towers::verify::verify(&p, &latin_square()), Err(TowersViolation::View { side: Direction::NORTH, index: 1, hint: 3, seen: 2 }));
+
+    p.north[1] = None;
+    p.east[2] = Some(1);
+    assert_eq!(towers::verify::verify(&p, &latin_square()), Err(TowersViolation::View { side: Direction::EAST, index: 2, hint: 1, seen: 2 }));
+}
+
+fn loopy_puzzle(grid: Vec<Vec<Option<u8>>>) -> loopy::puzzle::Puzzle {
+    return loopy::puzzle::Puzzle { width: grid[0].len(), height: grid.len(), difficulty: 0, grid };
+}
+
+#[test]
+fn loopy() {
+    // A loop around the single cell.
+    let square = Solution { horizontal: vec![vec![true], vec![true]], vertical: vec![vec![true, true]] };
+    assert_eq!(loopy::verify::verify(&loopy_puzzle(vec![vec![Some(4)]]), &square), Ok(()));
+    assert_eq!(loopy::verify::verify(&loopy_puzzle(vec![vec![Some(3)]]), &square), Err(LoopyViolation::CellCount { row: 0, column: 0, hint: 3, count: 4 }));
+    assert_eq!(loopy::verify::verify(&loopy_puzzle(vec![vec![None, None]]), &square), Err(LoopyViolation::WrongSize { width: 2, height: 1 }));
+
+    let p = loopy_puzzle(vec![vec![None]]);
+    let top = Solution { horizontal: vec![vec![true], vec![false]], vertical: vec![vec![false, false]] };
+    assert_eq!(loopy::verify::verify(&p, &top), Err(LoopyViolation::DeadEnd { row: 0, column: 0 }));
+    let nothing = Solution { horizontal: vec![vec![false], vec![false]], vertical: vec![vec![false, false]] };
+    assert_eq!(loopy::verify::verify(&p, &nothing), Err(LoopyViolation::NoLoop));
+
+    // Both cells of a 2 by 1 grid boxed in, so three edges meet on the top and bottom middle nodes.
+    let p = loopy_puzzle(vec![vec![None, None]]);
+    let boxes = Solution { horizontal: vec![vec![true, true], vec![true, true]], vertical: vec![vec![true, true, true]] };
+    assert_eq!(loopy::verify::verify(&p, &boxes), Err(LoopyViolation::Branch { row: 0, column: 1, edges: 3 }));
+
+    // Loops around the first and last cells of a 3 by 1 grid.
+    let p = loopy_puzzle(vec![vec![None, None, None]]);
+    let loops = Solution { horizontal: vec![vec![true, false, true], vec![true, false, true]], vertical: vec![vec![true, true, true, true]] };
+    assert_eq!(loopy::verify::verify(&p, &loops), Err(LoopyViolation::SeveralLoops { count: 2 }));
+}